@@ -0,0 +1,208 @@
+//! Group storage allocated with a custom allocator, requires a nightly compiler.
+use std::{
+    alloc::{Allocator, Global},
+    borrow::Borrow,
+    collections::{btree_set, BTreeSet},
+    ops::Bound,
+};
+
+use crate::{GroupLookup, GroupStorage, SortedGroups};
+
+/// `BTreeSet<E, A>` holding the elements of a group in memory from the allocator `A`, see
+/// [`SortedGroupsIn`].
+///
+/// Group storages are created empty with `A::default()`, so `A` is typically a handle to an arena
+/// shared by all groups: dropping the `SortedGroups` and then the arena releases every element at
+/// once. The vector of groups itself uses the global allocator.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BTreeSetIn<E, A: Allocator + Clone = Global>(BTreeSet<E, A>);
+
+impl<E, A: Allocator + Clone> BTreeSetIn<E, A> {
+    pub fn new_in(allocator: A) -> Self {
+        Self(BTreeSet::new_in(allocator))
+    }
+
+    pub fn as_set(&self) -> &BTreeSet<E, A> {
+        &self.0
+    }
+
+    pub fn into_set(self) -> BTreeSet<E, A> {
+        self.0
+    }
+}
+
+impl<E, A: Allocator + Clone + Default> Default for BTreeSetIn<E, A> {
+    fn default() -> Self {
+        Self::new_in(A::default())
+    }
+}
+
+impl<'a, E, A: Allocator + Clone> IntoIterator for &'a BTreeSetIn<E, A> {
+    type Item = &'a E;
+    type IntoIter = btree_set::Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<E, A> GroupStorage<E> for BTreeSetIn<E, A>
+where
+    E: Ord,
+    A: Allocator + Clone + Default,
+{
+    type Iter<'a>
+        = btree_set::Range<'a, E>
+    where
+        E: 'a,
+        A: 'a;
+    type IntoIter = btree_set::IntoIter<E, A>;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn insert_at(&mut self, element: E) -> Option<usize> {
+        if self.0.contains(&element) {
+            return None;
+        }
+        let rank = self.0.range(..&element).count();
+        self.0.insert(element);
+        Some(rank)
+    }
+
+    fn insert(&mut self, element: E) -> bool {
+        self.0.insert(element)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        if self.0.last().is_some_and(|last| last >= &element) {
+            return Err(element);
+        }
+        self.0.insert(element);
+        Ok(())
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.range(..)
+    }
+
+    fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+        match (self.nth(n), self.0.last()) {
+            (Some(first), _) => self.0.range(first..),
+            (None, Some(last)) => self.0.range((Bound::Excluded(last), Bound::Unbounded)),
+            (None, None) => self.0.range(..),
+        }
+    }
+
+    fn into_elements(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+
+    // As for `BTreeSet`, walk the set from the closest end
+    fn nth(&self, n: usize) -> Option<&E> {
+        if n < self.0.len() / 2 {
+            self.0.iter().nth(n)
+        } else {
+            self.0.iter().nth_back(self.0.len().checked_sub(n + 1)?)
+        }
+    }
+
+    fn pop_first(&mut self) -> Option<E> {
+        self.0.pop_first()
+    }
+
+    fn pop_last(&mut self) -> Option<E> {
+        self.0.pop_last()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&E) -> bool) {
+        self.0.retain(f);
+    }
+}
+
+impl<E, Q, A> GroupLookup<E, Q> for BTreeSetIn<E, A>
+where
+    E: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+    A: Allocator + Clone + Default,
+{
+    fn contains(&self, element: &Q) -> bool {
+        self.0.contains(element)
+    }
+
+    fn take(&mut self, element: &Q) -> Option<E> {
+        self.0.take(element)
+    }
+
+    fn rank(&self, element: &Q) -> Option<usize> {
+        if !self.0.contains(element) {
+            return None;
+        }
+        Some(
+            self.0
+                .range::<Q, _>((Bound::Unbounded, Bound::Excluded(element)))
+                .count(),
+        )
+    }
+
+    fn count(&self, element: &Q) -> usize {
+        usize::from(self.0.contains(element))
+    }
+
+    fn lower_bound(&self, element: &Q) -> usize {
+        self.0
+            .range::<Q, _>((Bound::Unbounded, Bound::Excluded(element)))
+            .count()
+    }
+}
+
+/// [`SortedGroups`] holding the elements of each group in memory from the allocator `A`, see
+/// [`BTreeSetIn`].
+pub type SortedGroupsIn<G, E, A> = SortedGroups<G, E, BTreeSetIn<E, A>>;
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        alloc::{AllocError, Allocator, Global, Layout},
+        ptr::NonNull,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+    // Allocator counting live allocations, standing in for an arena
+    #[derive(Clone, Copy, Default)]
+    struct Counting;
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            ALLOCATED.fetch_add(1, Ordering::SeqCst);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            ALLOCATED.fetch_sub(1, Ordering::SeqCst);
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    #[test]
+    fn test_custom_allocator() {
+        let mut sorted_groups =
+            SortedGroupsIn::<i32, i32, Counting>::new_with_storage(vec![11, 12, 21], |e| e / 10);
+        sorted_groups.insert(13);
+        assert!(ALLOCATED.load(Ordering::SeqCst) > 0);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12), (&1, &13), (&2, &21)]
+        );
+        assert_eq!(sorted_groups.position_of(&1, &13), Some(2));
+        assert_eq!(sorted_groups.split_off(&2).len(), 1);
+
+        drop(sorted_groups);
+        assert_eq!(ALLOCATED.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,50 @@
+//! `Arbitrary` implementation, enabled by the `arbitrary` feature.
+use arbitrary::{size_hint, Arbitrary, Result, Unstructured};
+
+use crate::{GroupStorage, SortedGroups};
+
+/// Builds a `SortedGroups` from arbitrary `(group, element)` pairs, as
+/// [`from_pairs`](SortedGroups::from_pairs) does, so no grouping function is available.
+impl<'a, G, E, S> Arbitrary<'a> for SortedGroups<G, E, S>
+where
+    G: Arbitrary<'a> + Ord,
+    E: Arbitrary<'a>,
+    S: GroupStorage<E>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let pairs = u.arbitrary_iter::<(G, E)>()?.collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_pairs_with_storage(pairs))
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        let pairs = u
+            .arbitrary_take_rest_iter::<(G, E)>()?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_pairs_with_storage(pairs))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        size_hint::and(<usize as Arbitrary>::size_hint(depth), (0, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_arbitrary() {
+        // Continuation flag followed by a group and an element, for every pair
+        let bytes = (0..32u8).flat_map(|i| [1, i % 4, i, 0]).collect::<Vec<_>>();
+        let mut u = Unstructured::new(&bytes);
+        let sorted_groups = SortedGroups::<u8, u16>::arbitrary(&mut u).unwrap();
+
+        assert_eq!(sorted_groups.groups_len(), 4);
+        assert_eq!(sorted_groups.len(), sorted_groups.iter().count());
+        assert!(sorted_groups
+            .iter_groups()
+            .all(|(_, elements)| !elements.is_empty()));
+    }
+}
@@ -0,0 +1,112 @@
+//! Lock-free reads of snapshots swapped by a writer, enabled by the `arc-swap` feature.
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+
+use crate::{GroupStorage, SortedGroups};
+
+/// [`SortedGroups`] shared between readers loading immutable snapshots without locking and
+/// writers replacing the snapshot after a batch of mutations.
+///
+/// Each update copies the whole `SortedGroups`, so that readers are never blocked: mutations are
+/// best batched in a single [`update`](Self::update).
+pub struct SwappableSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    current: ArcSwap<SortedGroups<G, E, S>>,
+    // Serializes writers, so that concurrent updates are not lost
+    writer: Mutex<()>,
+}
+
+impl<G, E, S> SwappableSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    pub fn new(sorted_groups: SortedGroups<G, E, S>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(sorted_groups),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current snapshot, which is not affected by later updates.
+    pub fn load(&self) -> Arc<SortedGroups<G, E, S>> {
+        self.current.load_full()
+    }
+
+    /// Replaces the current snapshot with `sorted_groups`.
+    pub fn store(&self, sorted_groups: SortedGroups<G, E, S>) {
+        let _writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        self.current.store(Arc::new(sorted_groups));
+    }
+}
+
+impl<G, E, S> SwappableSortedGroups<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E> + Clone,
+{
+    /// Calls `f` on a copy of the current snapshot, then publishes the copy as the new snapshot
+    /// and returns the result of `f`.
+    ///
+    /// Updates are serialized, readers keep loading the previous snapshot until `f` returns. As
+    /// with [`Clone`], the copy doesn't keep the observer.
+    pub fn update<T>(&self, f: impl FnOnce(&mut SortedGroups<G, E, S>) -> T) -> T {
+        let _writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let mut sorted_groups = SortedGroups::clone(&self.current.load());
+        let result = f(&mut sorted_groups);
+        self.current.store(Arc::new(sorted_groups));
+        result
+    }
+}
+
+impl<G, E, S> From<SortedGroups<G, E, S>> for SwappableSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn from(sorted_groups: SortedGroups<G, E, S>) -> Self {
+        Self::new(sorted_groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn test_readers_load_snapshots() {
+        let swappable = Arc::new(SwappableSortedGroups::new(SortedGroups::<i32, i32>::new(
+            vec![11],
+            |e| e / 10,
+        )));
+        let before = swappable.load();
+
+        let writers = (0..4)
+            .map(|i| {
+                let swappable = swappable.clone();
+                thread::spawn(move || {
+                    swappable.update(|sorted_groups| sorted_groups.insert(20 + i))
+                })
+            })
+            .collect::<Vec<_>>();
+        for writer in writers {
+            assert!(writer.join().unwrap());
+        }
+
+        assert_eq!(before.len(), 1);
+        assert_eq!(swappable.load().len(), 5);
+        assert_eq!(swappable.load().group_len(&2), 4);
+    }
+}
@@ -0,0 +1,278 @@
+//! Construction with configurable policies, see [`SortedGroupsBuilder`].
+use std::{collections::BTreeSet, error::Error, fmt, marker::PhantomData, sync::Arc};
+
+use crate::{GroupLookup, SortedGroups};
+
+/// What to do with an element equal to one already in its group, see
+/// [`SortedGroupsBuilder::on_duplicate`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The element already present is kept, as with [`SortedGroups::insert`]
+    #[default]
+    KeepFirst,
+    /// The new element replaces the one already present, as with [`SortedGroups::replace`]
+    KeepLast,
+    /// Building fails with a [`DuplicateError`]
+    Error,
+}
+
+/// Error returned when an element is equal to one already in its group, holding the rejected
+/// element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateError<E> {
+    element: E,
+}
+
+impl<E> DuplicateError<E> {
+    pub(crate) fn new(element: E) -> Self {
+        Self { element }
+    }
+
+    /// Returns the rejected element.
+    pub fn element(&self) -> &E {
+        &self.element
+    }
+
+    /// Takes ownership of the rejected element.
+    pub fn into_element(self) -> E {
+        self.element
+    }
+}
+
+impl<E> fmt::Display for DuplicateError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("element already exists in its group")
+    }
+}
+
+impl<E: fmt::Debug> Error for DuplicateError<E> {}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Returns a builder to configure how a `SortedGroups` is built.
+    pub fn builder() -> SortedGroupsBuilder<G, E> {
+        SortedGroupsBuilder::new()
+    }
+}
+
+/// Builder of a [`SortedGroups`] with configurable policies, see [`SortedGroups::builder`].
+///
+/// Groups are sorted in ascending order unless
+/// [`descending_groups`](SortedGroupsBuilder::descending_groups) is set.
+pub struct SortedGroupsBuilder<G, E, S = BTreeSet<E>> {
+    keep_empty_groups: bool,
+    descending_groups: bool,
+    on_duplicate: DuplicatePolicy,
+    // Number of groups to allocate room for upfront
+    groups_capacity: usize,
+    _types: PhantomData<(G, E, S)>,
+}
+
+impl<G, E, S> SortedGroupsBuilder<G, E, S>
+where
+    G: Ord,
+    S: GroupLookup<E, E>,
+{
+    /// Creates a builder with the default policies of [`SortedGroups::new`].
+    pub fn new() -> Self {
+        Self {
+            keep_empty_groups: false,
+            descending_groups: false,
+            on_duplicate: DuplicatePolicy::default(),
+            groups_capacity: 0,
+            _types: PhantomData,
+        }
+    }
+
+    /// Sets whether groups left empty by a removal are kept, see
+    /// [`SortedGroups::set_keep_empty_groups`].
+    pub fn keep_empty_groups(mut self, keep_empty_groups: bool) -> Self {
+        self.keep_empty_groups = keep_empty_groups;
+        self
+    }
+
+    /// Sets whether groups are sorted in descending order, which every positional API then
+    /// follows, see [`SortedGroups::reverse_groups`].
+    pub fn descending_groups(mut self, descending_groups: bool) -> Self {
+        self.descending_groups = descending_groups;
+        self
+    }
+
+    /// Sets what to do with elements equal to one already in their group.
+    pub fn on_duplicate(mut self, on_duplicate: DuplicatePolicy) -> Self {
+        self.on_duplicate = on_duplicate;
+        self
+    }
+
+    /// Allocates room for `groups_capacity` groups upfront.
+    pub fn groups_capacity(mut self, groups_capacity: usize) -> Self {
+        self.groups_capacity = groups_capacity;
+        self
+    }
+
+    /// Builds a `SortedGroups` from `elements`, see [`SortedGroups::new`].
+    ///
+    /// Fails on the first duplicate element with [`DuplicatePolicy::Error`].
+    pub fn build(
+        self,
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+    ) -> Result<SortedGroups<G, E, S>, DuplicateError<E>> {
+        let mut sorted_groups = SortedGroups::from_parts(
+            Vec::with_capacity(self.groups_capacity),
+            Some(Arc::new(group_from_element)),
+        );
+        sorted_groups.keep_empty_groups = self.keep_empty_groups;
+        sorted_groups.descending_groups = self.descending_groups;
+        sorted_groups.debug_follow_grouping();
+        for element in elements {
+            match self.on_duplicate {
+                DuplicatePolicy::KeepFirst => {
+                    sorted_groups.insert(element);
+                }
+                DuplicatePolicy::KeepLast => {
+                    sorted_groups.replace(element);
+                }
+                DuplicatePolicy::Error => sorted_groups.try_insert(element)?,
+            }
+        }
+        Ok(sorted_groups)
+    }
+}
+
+impl<G, E, S> Default for SortedGroupsBuilder<G, E, S>
+where
+    G: Ord,
+    S: GroupLookup<E, E>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G, E, S> Clone for SortedGroupsBuilder<G, E, S> {
+    fn clone(&self) -> Self {
+        Self {
+            keep_empty_groups: self.keep_empty_groups,
+            descending_groups: self.descending_groups,
+            on_duplicate: self.on_duplicate,
+            groups_capacity: self.groups_capacity,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<G, E, S> fmt::Debug for SortedGroupsBuilder<G, E, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedGroupsBuilder")
+            .field("keep_empty_groups", &self.keep_empty_groups)
+            .field("descending_groups", &self.descending_groups)
+            .field("on_duplicate", &self.on_duplicate)
+            .field("groups_capacity", &self.groups_capacity)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compares by `key` only, so that equal elements can be told apart by `value`
+    #[derive(Debug)]
+    struct Record {
+        key: i32,
+        value: &'static str,
+    }
+
+    impl PartialEq for Record {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for Record {}
+
+    impl PartialOrd for Record {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Record {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                key: 11,
+                value: "first",
+            },
+            Record {
+                key: 21,
+                value: "other",
+            },
+            Record {
+                key: 11,
+                value: "last",
+            },
+        ]
+    }
+
+    fn build(
+        on_duplicate: DuplicatePolicy,
+    ) -> Result<SortedGroups<i32, Record>, DuplicateError<Record>> {
+        SortedGroups::builder()
+            .on_duplicate(on_duplicate)
+            .groups_capacity(2)
+            .build(records(), |record| record.key / 10)
+    }
+
+    #[test]
+    fn test_builder_duplicate_policies() {
+        let first = build(DuplicatePolicy::KeepFirst).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].value, "first");
+
+        let last = build(DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].value, "last");
+
+        let error = build(DuplicatePolicy::Error).unwrap_err();
+        assert_eq!(error.into_element().value, "last");
+    }
+
+    #[test]
+    fn test_builder_keep_empty_groups() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::builder()
+            .keep_empty_groups(true)
+            .build([11, 21], |e| e / 10)
+            .unwrap();
+
+        assert!(sorted_groups.keep_empty_groups());
+        sorted_groups.remove(&1, &11);
+        assert_eq!(sorted_groups.groups_len(), 2);
+    }
+
+    #[test]
+    fn test_builder_descending_groups() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::builder()
+            .descending_groups(true)
+            .build([11, 31, 21, 12], |e| e / 10)
+            .unwrap();
+
+        assert!(sorted_groups.descending_groups());
+        sorted_groups.insert(41);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&4, &41), (&3, &31), (&2, &21), (&1, &11), (&1, &12)]
+        );
+        assert_eq!(sorted_groups.get(1), Some((&3, &31)));
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+    }
+}
@@ -0,0 +1,703 @@
+//! Groups and elements ordered by comparators instead of `Ord`, see [`SortedGroupsBy`].
+use std::{borrow::Borrow, cmp::Ordering, fmt, sync::Arc};
+
+use crate::{Group, GroupFn, GroupLookup, GroupStorage, SortedGroups};
+
+/// Comparator of values of type `T`, implemented by closures and functions comparing two `&T`.
+///
+/// Implementing it for borrowed forms of a type too, e.g. for `str` along with `String`, allows
+/// looking up by these forms, as `Ord` keys do through [`Borrow`].
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+impl<T, F> Compare<T> for F
+where
+    T: ?Sized,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// Elements sorted by group, where groups and elements are ordered by comparators rather than by
+/// their `Ord` implementation.
+///
+/// Elements of a group comparing equal are deduplicated, as in [`SortedGroups`], on which this is
+/// built: lookups by group and element are `O(log n)`, as is [`get`](Self::get) once the offsets
+/// of groups are computed.
+pub struct SortedGroupsBy<G, E, CG, CE>
+where
+    CG: Compare<G>,
+    CE: Compare<E>,
+{
+    // Groups ordered by `compare_groups`, each one holding its elements ordered by
+    // `compare_elements`
+    inner: SortedGroups<ByGroup<G, CG>, E, SortedVecBy<E, CE>>,
+    group_from_element: GroupFn<E, G>,
+    compare_groups: Arc<CG>,
+    compare_elements: Arc<CE>,
+}
+
+impl<G, E, CG, CE> SortedGroupsBy<G, E, CG, CE>
+where
+    CG: Compare<G>,
+    CE: Compare<E>,
+{
+    /// Creates a new `SortedGroupsBy` from `elements`, ordering groups with `compare_groups` and
+    /// the elements of each group with `compare_elements`.
+    ///
+    /// `group_from_element` is kept to compute the group of elements inserted later on.
+    pub fn new(
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+        compare_groups: CG,
+        compare_elements: CE,
+    ) -> Self
+    where
+        CG: Fn(&G, &G) -> Ordering,
+        CE: Fn(&E, &E) -> Ordering,
+    {
+        Self::with_comparators(
+            elements,
+            group_from_element,
+            compare_groups,
+            compare_elements,
+        )
+    }
+
+    /// Same as [`new`](Self::new) with any [`Compare`] implementations, such as comparators
+    /// that also compare borrowed forms of groups or elements.
+    pub fn with_comparators(
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+        compare_groups: CG,
+        compare_elements: CE,
+    ) -> Self {
+        let mut sorted_groups = Self {
+            inner: SortedGroups::from_parts(Vec::new(), None),
+            group_from_element: Arc::new(group_from_element),
+            compare_groups: Arc::new(compare_groups),
+            compare_elements: Arc::new(compare_elements),
+        };
+        for element in elements {
+            sorted_groups.insert(element);
+        }
+        sorted_groups
+    }
+
+    /// Sets whether groups left empty by a removal are kept instead of being dropped, see
+    /// [`SortedGroups::set_keep_empty_groups`].
+    pub fn set_keep_empty_groups(&mut self, keep_empty_groups: bool) {
+        self.inner.set_keep_empty_groups(keep_empty_groups);
+    }
+
+    /// Returns whether groups left empty by a removal are kept, see
+    /// [`set_keep_empty_groups`](Self::set_keep_empty_groups).
+    pub fn keep_empty_groups(&self) -> bool {
+        self.inner.keep_empty_groups()
+    }
+
+    /// Adds `group` without any element, if it doesn't exist yet, see
+    /// [`SortedGroups::add_group`].
+    ///
+    /// Returns whether the group was newly added.
+    pub fn add_group(&mut self, group: G) -> bool {
+        match self.group_index(&group) {
+            Ok(_) => false,
+            Err(index) => {
+                self.insert_group(index, group);
+                true
+            }
+        }
+    }
+
+    /// Inserts `element` in its group, creating the group if needed.
+    ///
+    /// Returns whether the element was newly inserted.
+    pub fn insert(&mut self, element: E) -> bool {
+        let group = (self.group_from_element)(&element);
+        self.insert_with_group(group, element)
+    }
+
+    /// Inserts `element` in `group`, bypassing the grouping function.
+    ///
+    /// Returns whether the element was newly inserted.
+    pub fn insert_with_group(&mut self, group: G, element: E) -> bool {
+        let index = match self.group_index(&group) {
+            Ok(index) => index,
+            Err(index) => {
+                self.insert_group(index, group);
+                index
+            }
+        };
+        self.inner.insert_in_group(index, element)
+    }
+
+    /// Removes `element` from `group`, dropping the group if it becomes empty unless
+    /// [`keep_empty_groups`](Self::keep_empty_groups) is set.
+    ///
+    /// Returns whether the element was present.
+    pub fn remove<Q, R>(&mut self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+        E: Borrow<R>,
+        CE: Compare<R>,
+        R: ?Sized,
+    {
+        self.take(group, element).is_some()
+    }
+
+    /// Removes and returns the element equal to `element` from `group`, dropping the group if it
+    /// becomes empty unless [`keep_empty_groups`](Self::keep_empty_groups) is set.
+    pub fn take<Q, R>(&mut self, group: &Q, element: &R) -> Option<E>
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+        E: Borrow<R>,
+        CE: Compare<R>,
+        R: ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        let element = self.inner.take_from_group(index, element)?;
+        self.inner.drop_group_if_empty(index);
+        Some(element)
+    }
+
+    /// Returns whether `group` contains `element`.
+    pub fn contains<Q, R>(&self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+        E: Borrow<R>,
+        CE: Compare<R>,
+        R: ?Sized,
+    {
+        self.group_elements(group)
+            .is_some_and(|elements| elements.contains(element))
+    }
+
+    /// Returns whether `group` exists.
+    pub fn contains_group<Q>(&self, group: &Q) -> bool
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.group_index(group).is_ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn groups_len(&self) -> usize {
+        self.inner.groups_len()
+    }
+
+    /// Returns the number of elements in `group`.
+    pub fn group_len<Q>(&self, group: &Q) -> usize
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.group_elements(group).map_or(0, SortedVecBy::len)
+    }
+
+    /// Returns the element at the flat `index`, along with its group.
+    ///
+    /// As with [`SortedGroups::get`], the group is found by binary search over the offsets of
+    /// groups.
+    pub fn get(&self, index: usize) -> Option<(&G, &E)> {
+        let (group, element) = self.inner.get(index)?;
+        Some((&group.group, element))
+    }
+
+    /// Returns an iterator over groups and elements.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&G, &E)> {
+        self.inner
+            .iter()
+            .map(|(group, element)| (&group.group, element))
+    }
+
+    /// Returns an iterator over groups and their elements.
+    pub fn iter_groups(&self) -> impl Iterator<Item = (&G, Group<'_, E, SortedVecBy<E, CE>>)> {
+        self.inner
+            .groups
+            .iter()
+            .map(|(group, elements)| (&group.group, Group::new(elements)))
+    }
+
+    /// Returns an iterator over the elements of `group`, which is empty if the group doesn't
+    /// exist.
+    pub fn iter_group<Q>(&self, group: &Q) -> impl Iterator<Item = &E>
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.group_elements(group)
+            .into_iter()
+            .flat_map(|elements| &elements.elements)
+    }
+
+    fn group_elements<Q>(&self, group: &Q) -> Option<&SortedVecBy<E, CE>>
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        Some(&self.inner.groups[index].1)
+    }
+
+    // Every group is created here, so that its storage orders elements with the comparator
+    fn insert_group(&mut self, index: usize, group: G) {
+        let group = ByGroup {
+            group,
+            compare: self.compare_groups.clone(),
+        };
+        let elements = SortedVecBy {
+            elements: Vec::new(),
+            compare: Some(self.compare_elements.clone()),
+        };
+        self.inner.insert_group_with(index, group, elements);
+    }
+
+    fn group_index<Q>(&self, group: &Q) -> Result<usize, usize>
+    where
+        G: Borrow<Q>,
+        CG: Compare<Q>,
+        Q: ?Sized,
+    {
+        self.inner
+            .groups
+            .binary_search_by(|(g, _)| self.compare_groups.compare(g.group.borrow(), group))
+    }
+}
+
+impl<G, E, CG, CE> Clone for SortedGroupsBy<G, E, CG, CE>
+where
+    G: Clone,
+    E: Clone,
+    CG: Compare<G>,
+    CE: Compare<E>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            group_from_element: self.group_from_element.clone(),
+            compare_groups: self.compare_groups.clone(),
+            compare_elements: self.compare_elements.clone(),
+        }
+    }
+}
+
+impl<G, E, CG, CE> fmt::Debug for SortedGroupsBy<G, E, CG, CE>
+where
+    G: fmt::Debug,
+    E: fmt::Debug,
+    CG: Compare<G>,
+    CE: Compare<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedGroupsBy")
+            .field("groups", &self.inner.groups)
+            .finish_non_exhaustive()
+    }
+}
+
+// Group ordered by the comparator of its instance instead of its `Ord` implementation
+struct ByGroup<G, CG> {
+    group: G,
+    compare: Arc<CG>,
+}
+
+impl<G, CG: Compare<G>> Ord for ByGroup<G, CG> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.compare.compare(&self.group, &other.group)
+    }
+}
+
+impl<G, CG: Compare<G>> PartialOrd for ByGroup<G, CG> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<G, CG: Compare<G>> PartialEq for ByGroup<G, CG> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<G, CG: Compare<G>> Eq for ByGroup<G, CG> {}
+
+impl<G: Clone, CG> Clone for ByGroup<G, CG> {
+    fn clone(&self) -> Self {
+        Self {
+            group: self.group.clone(),
+            compare: self.compare.clone(),
+        }
+    }
+}
+
+impl<G: fmt::Debug, CG> fmt::Debug for ByGroup<G, CG> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.group.fmt(f)
+    }
+}
+
+/// Storage of the groups of a [`SortedGroupsBy`], holding elements sorted by its element
+/// comparator, deduplicated.
+///
+/// Only `SortedGroupsBy` creates storages along with its comparator. Storages created by
+/// `Default`, as required by [`GroupStorage`], have none: they only accept elements pushed in
+/// order from another storage, and inserting into them panics.
+pub struct SortedVecBy<E, CE> {
+    elements: Vec<E>,
+    compare: Option<Arc<CE>>,
+}
+
+impl<E, CE> SortedVecBy<E, CE> {
+    // Position of the first element not ordered before `element`, and whether it is equal
+    fn search<Q>(&self, element: &Q) -> (usize, bool)
+    where
+        E: Borrow<Q>,
+        CE: Compare<Q>,
+        Q: ?Sized,
+    {
+        let Some(compare) = &self.compare else {
+            return (self.elements.len(), false);
+        };
+        let index = self
+            .elements
+            .partition_point(|e| compare.compare(e.borrow(), element) == Ordering::Less);
+        let found = self
+            .elements
+            .get(index)
+            .is_some_and(|e| compare.compare(e.borrow(), element) == Ordering::Equal);
+        (index, found)
+    }
+
+    fn with_elements(&self, elements: Vec<E>) -> Self {
+        Self {
+            elements,
+            compare: self.compare.clone(),
+        }
+    }
+}
+
+impl<E, CE> Default for SortedVecBy<E, CE> {
+    fn default() -> Self {
+        Self {
+            elements: Vec::new(),
+            compare: None,
+        }
+    }
+}
+
+impl<E: Clone, CE> Clone for SortedVecBy<E, CE> {
+    fn clone(&self) -> Self {
+        self.with_elements(self.elements.clone())
+    }
+}
+
+impl<E: fmt::Debug, CE> fmt::Debug for SortedVecBy<E, CE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.elements.fmt(f)
+    }
+}
+
+impl<E, CE: Compare<E>> GroupStorage<E> for SortedVecBy<E, CE> {
+    type Iter<'a>
+        = std::slice::Iter<'a, E>
+    where
+        Self: 'a,
+        E: 'a;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn insert_at(&mut self, element: E) -> Option<usize> {
+        assert!(
+            self.compare.is_some(),
+            "SortedVecBy has no comparator, its groups must be created by SortedGroupsBy"
+        );
+        let (index, found) = self.search(&element);
+        if found {
+            return None;
+        }
+        self.elements.insert(index, element);
+        Some(index)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        let ordered = match (&self.compare, self.elements.last()) {
+            (Some(compare), Some(last)) => compare.compare(last, &element) == Ordering::Less,
+            _ => true,
+        };
+        if !ordered {
+            return Err(element);
+        }
+        self.elements.push(element);
+        Ok(())
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.elements.iter()
+    }
+
+    fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+        self.elements[n.min(self.elements.len())..].iter()
+    }
+
+    fn into_elements(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+
+    fn nth(&self, n: usize) -> Option<&E> {
+        self.elements.get(n)
+    }
+
+    fn pop_first(&mut self) -> Option<E> {
+        if self.elements.is_empty() {
+            return None;
+        }
+        Some(self.elements.remove(0))
+    }
+
+    fn pop_last(&mut self) -> Option<E> {
+        self.elements.pop()
+    }
+
+    fn remove_nth(&mut self, n: usize) -> Option<E> {
+        (n < self.elements.len()).then(|| self.elements.remove(n))
+    }
+
+    fn retain(&mut self, f: impl FnMut(&E) -> bool) {
+        self.elements.retain(f);
+    }
+
+    fn split_off(&mut self, n: usize) -> Self {
+        let tail = self.elements.split_off(n.min(self.elements.len()));
+        self.with_elements(tail)
+    }
+
+    fn extract_if(&mut self, mut pred: impl FnMut(&E) -> bool) -> Self {
+        let extracted = self
+            .elements
+            .extract_if(.., |element| pred(element))
+            .collect();
+        self.with_elements(extracted)
+    }
+}
+
+impl<E, CE, Q> GroupLookup<E, Q> for SortedVecBy<E, CE>
+where
+    E: Borrow<Q>,
+    CE: Compare<E> + Compare<Q>,
+    Q: ?Sized,
+{
+    fn contains(&self, element: &Q) -> bool {
+        self.search(element).1
+    }
+
+    fn take(&mut self, element: &Q) -> Option<E> {
+        let index = self.rank(element)?;
+        Some(self.elements.remove(index))
+    }
+
+    fn rank(&self, element: &Q) -> Option<usize> {
+        let (index, found) = self.search(element);
+        found.then_some(index)
+    }
+
+    fn count(&self, element: &Q) -> usize {
+        usize::from(self.search(element).1)
+    }
+
+    fn lower_bound(&self, element: &Q) -> usize {
+        self.search(element).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Post {
+        date: u32,
+        title: &'static str,
+    }
+
+    type Posts =
+        SortedGroupsBy<u32, Post, fn(&u32, &u32) -> Ordering, fn(&Post, &Post) -> Ordering>;
+
+    fn posts() -> Posts {
+        SortedGroupsBy::new(
+            vec![
+                Post {
+                    date: 1,
+                    title: "b",
+                },
+                Post {
+                    date: 2,
+                    title: "a",
+                },
+                Post {
+                    date: 1,
+                    title: "A",
+                },
+            ],
+            |post| post.date,
+            |a, b| b.cmp(a),
+            |a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+        )
+    }
+
+    #[test]
+    fn test_comparators() {
+        let sorted_groups = posts();
+
+        assert_eq!(
+            sorted_groups
+                .iter()
+                .map(|(date, post)| (*date, post.title))
+                .collect::<Vec<_>>(),
+            vec![(2, "a"), (1, "A"), (1, "b")]
+        );
+        assert_eq!(sorted_groups.get(2).map(|(_, post)| post.title), Some("b"));
+        assert_eq!(sorted_groups.get(3), None);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut sorted_groups = posts();
+
+        assert!(!sorted_groups.insert(Post {
+            date: 1,
+            title: "B"
+        }));
+        assert!(sorted_groups.contains(
+            &1,
+            &Post {
+                date: 1,
+                title: "a"
+            }
+        ));
+        assert!(sorted_groups.remove(
+            &2,
+            &Post {
+                date: 2,
+                title: "A"
+            }
+        ));
+        assert!(!sorted_groups.contains_group(&2));
+        assert_eq!(sorted_groups.len(), 2);
+        assert_eq!(sorted_groups.groups_len(), 1);
+    }
+
+    #[test]
+    fn test_get_follows_mutations() {
+        let mut sorted_groups = posts();
+        assert_eq!(sorted_groups.get(1).map(|(_, post)| post.title), Some("A"));
+
+        sorted_groups.insert(Post {
+            date: 3,
+            title: "c",
+        });
+        sorted_groups.set_keep_empty_groups(true);
+        sorted_groups.remove(
+            &2,
+            &Post {
+                date: 2,
+                title: "a",
+            },
+        );
+        assert_eq!(sorted_groups.groups_len(), 3);
+        assert_eq!(sorted_groups.group_len(&2), 0);
+        assert_eq!(
+            sorted_groups
+                .iter()
+                .map(|(date, post)| (*date, post.title))
+                .collect::<Vec<_>>(),
+            vec![(3, "c"), (1, "A"), (1, "b")]
+        );
+        assert_eq!(sorted_groups.get(1).map(|(_, post)| post.title), Some("A"));
+        assert_eq!(sorted_groups.inner.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_insert_in_added_group() {
+        let mut sorted_groups = posts();
+
+        assert!(sorted_groups.add_group(3));
+        assert!(!sorted_groups.add_group(1));
+        for title in ["z", "C", "m"] {
+            sorted_groups.insert(Post { date: 3, title });
+        }
+        assert_eq!(
+            sorted_groups
+                .iter_groups()
+                .map(|(date, posts)| (*date, posts.iter().map(|post| post.title).collect()))
+                .collect::<Vec<(_, Vec<_>)>>(),
+            vec![
+                (3, vec!["C", "m", "z"]),
+                (2, vec!["a"]),
+                (1, vec!["A", "b"])
+            ]
+        );
+        assert_eq!(sorted_groups.get(1).map(|(_, post)| post.title), Some("m"));
+    }
+
+    // Compares case insensitively, owned and borrowed strings alike
+    struct CaseInsensitive;
+
+    impl Compare<str> for CaseInsensitive {
+        fn compare(&self, a: &str, b: &str) -> Ordering {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        }
+    }
+
+    impl Compare<String> for CaseInsensitive {
+        fn compare(&self, a: &String, b: &String) -> Ordering {
+            self.compare(a.as_str(), b.as_str())
+        }
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut sorted_groups = SortedGroupsBy::with_comparators(
+            ["Apple", "avocado", "Banana"].map(String::from),
+            |e| e[..1].to_lowercase(),
+            CaseInsensitive,
+            CaseInsensitive,
+        );
+
+        assert!(sorted_groups.contains_group("A"));
+        assert!(sorted_groups.contains("a", "APPLE"));
+        assert_eq!(
+            sorted_groups.iter_group("b").collect::<Vec<_>>(),
+            ["Banana"]
+        );
+        assert_eq!(
+            sorted_groups.take("a", "Avocado").as_deref(),
+            Some("avocado")
+        );
+        assert_eq!(sorted_groups.len(), 2);
+    }
+}
@@ -0,0 +1,80 @@
+//! Iteration in fixed-size pieces, see [`SortedGroups::chunks`].
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns an iterator over chunks of `size` elements along with their group, in iteration
+    /// order, the last chunk holding the remaining elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = Vec<(&G, &E)>> {
+        assert!(size != 0, "chunk size must be non-zero");
+        let mut iter = self.iter();
+        std::iter::from_fn(move || {
+            let chunk = iter.by_ref().take(size).collect::<Vec<_>>();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// Returns an iterator over chunks of whole groups holding at most `size` elements along with
+    /// their group, except for groups larger than `size` which get a chunk of their own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn group_chunks(&self, size: usize) -> impl Iterator<Item = Vec<(&G, &E)>> {
+        assert!(size != 0, "chunk size must be non-zero");
+        let mut groups = self
+            .groups
+            .iter()
+            .filter(|(_, elements)| !elements.is_empty())
+            .map(|(group, elements)| (group, elements))
+            .peekable();
+        std::iter::from_fn(move || {
+            let mut chunk = Vec::new();
+            while let Some(&(group, elements)) = groups.peek() {
+                if !chunk.is_empty() && chunk.len() + elements.len() > size {
+                    break;
+                }
+                chunk.extend(elements.iter().map(|element| (group, element)));
+                groups.next();
+            }
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    fn elements<'a>(chunks: impl Iterator<Item = Vec<(&'a i32, &'a i32)>>) -> Vec<Vec<i32>> {
+        chunks
+            .map(|chunk| chunk.into_iter().map(|(_, element)| *element).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_chunks() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21, 31, 32, 33, 34], |e| e / 10);
+
+        assert_eq!(
+            elements(sorted_groups.chunks(3)),
+            vec![vec![11, 12, 13], vec![21, 31, 32], vec![33, 34]]
+        );
+        assert_eq!(
+            elements(sorted_groups.group_chunks(3)),
+            vec![vec![11, 12, 13], vec![21], vec![31, 32, 33, 34]]
+        );
+        assert_eq!(
+            elements(sorted_groups.group_chunks(5)),
+            vec![vec![11, 12, 13, 21], vec![31, 32, 33, 34]]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+//! Bidirectional navigation from a flat index, see [`SortedGroups::cursor_at`].
+use std::{borrow::Borrow, collections::BTreeSet, fmt};
+
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns a cursor on the first element, see [`cursor_at`](Self::cursor_at).
+    pub fn cursor(&self) -> Cursor<'_, G, E, S> {
+        self.cursor_at(0)
+    }
+
+    /// Returns a cursor on the element at the flat `index`, or past the last element if `index`
+    /// is out of bounds.
+    pub fn cursor_at(&self, index: usize) -> Cursor<'_, G, E, S> {
+        Cursor {
+            sorted_groups: self,
+            index: index.min(self.len),
+        }
+    }
+
+    /// Returns a cursor on the first element that can remove and insert elements while moving,
+    /// see [`cursor_mut_at`](Self::cursor_mut_at).
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, G, E, S> {
+        self.cursor_mut_at(0)
+    }
+
+    /// Returns a cursor on the element at the flat `index` that can remove and insert elements
+    /// while moving, see [`cursor_at`](Self::cursor_at).
+    pub fn cursor_mut_at(&mut self, index: usize) -> CursorMut<'_, G, E, S> {
+        let index = index.min(self.len);
+        CursorMut {
+            sorted_groups: self,
+            index,
+        }
+    }
+}
+
+/// Cursor moving over the elements of a [`SortedGroups`] in both directions, see
+/// [`SortedGroups::cursor_at`].
+///
+/// The cursor is either on an element or past the last one. Each move locates the element the
+/// same way as [`get`](SortedGroups::get), without iterating from the start.
+pub struct Cursor<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a SortedGroups<G, E, S>,
+    // Flat index of the current element, `len` when past the last element
+    index: usize,
+}
+
+impl<'a, G, E, S> Cursor<'a, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns the current element along with its group, or `None` if past the last element.
+    pub fn current(&self) -> Option<(&'a G, &'a E)> {
+        self.sorted_groups.get(self.index)
+    }
+
+    /// Returns the flat index of the current element, which is the number of elements if past the
+    /// last element.
+    pub fn position(&self) -> usize {
+        self.index
+    }
+
+    /// Moves to the next element and returns it, or moves past the last element and returns
+    /// `None` if there is none.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&'a G, &'a E)> {
+        self.index = (self.index + 1).min(self.sorted_groups.len);
+        self.current()
+    }
+
+    /// Moves to the previous element and returns it, or stays in place and returns `None` if on
+    /// the first element.
+    pub fn prev(&mut self) -> Option<(&'a G, &'a E)> {
+        self.index = self.index.checked_sub(1)?;
+        self.current()
+    }
+
+    /// Moves to the first element of `group`, or of the next group if it has no elements, and
+    /// returns it.
+    pub fn seek_group<Q>(&mut self, group: &Q) -> Option<(&'a G, &'a E)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let sorted_groups = self.sorted_groups;
+        let group_index = sorted_groups.groups_before(group);
+        self.index = sorted_groups.offset(group_index);
+        self.current()
+    }
+}
+
+impl<G, E, S> Clone for Cursor<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            sorted_groups: self.sorted_groups,
+            index: self.index,
+        }
+    }
+}
+
+impl<G, E, S> fmt::Debug for Cursor<'_, G, E, S>
+where
+    G: Ord + fmt::Debug,
+    E: fmt::Debug,
+    S: GroupStorage<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("index", &self.index)
+            .field("current", &self.current())
+            .finish()
+    }
+}
+
+/// Cursor moving over the elements of a [`SortedGroups`] in both directions while editing it, see
+/// [`SortedGroups::cursor_mut_at`].
+pub struct CursorMut<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a mut SortedGroups<G, E, S>,
+    // Flat index of the current element, `len` when past the last element
+    index: usize,
+}
+
+impl<G, E, S> CursorMut<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns a read-only cursor at the same position.
+    pub fn as_cursor(&self) -> Cursor<'_, G, E, S> {
+        Cursor {
+            sorted_groups: self.sorted_groups,
+            index: self.index,
+        }
+    }
+
+    /// Returns the current element along with its group, or `None` if past the last element.
+    pub fn current(&self) -> Option<(&G, &E)> {
+        self.sorted_groups.get(self.index)
+    }
+
+    /// Returns the flat index of the current element, which is the number of elements if past the
+    /// last element.
+    pub fn position(&self) -> usize {
+        self.index
+    }
+
+    /// Moves to the next element and returns it, see [`Cursor::next`].
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<(&G, &E)> {
+        self.index = (self.index + 1).min(self.sorted_groups.len);
+        self.current()
+    }
+
+    /// Moves to the previous element and returns it, see [`Cursor::prev`].
+    pub fn prev(&mut self) -> Option<(&G, &E)> {
+        self.index = self.index.checked_sub(1)?;
+        self.current()
+    }
+
+    /// Moves to the first element of `group` and returns it, see [`Cursor::seek_group`].
+    pub fn seek_group<Q>(&mut self, group: &Q) -> Option<(&G, &E)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut cursor = self.as_cursor();
+        cursor.seek_group(group);
+        self.index = cursor.position();
+        self.current()
+    }
+
+    /// Removes and returns the current element, dropping its group if it becomes empty.
+    ///
+    /// The cursor moves to the next element, or past the last element if there is none.
+    pub fn remove_current(&mut self) -> Option<E> {
+        let (group_index, rank) = self.sorted_groups.locate_group(self.index)?;
+        let element = self.sorted_groups.remove_from_group(group_index, rank)?;
+        self.sorted_groups.drop_group_if_empty(group_index);
+        Some(element)
+    }
+
+    /// Inserts `element` in its group, creating the group if needed, and stays on the current
+    /// element.
+    ///
+    /// There is no insertion after the current element: the element is placed at its position in
+    /// the order of groups and elements, wherever that is relative to the cursor, so it is
+    /// reached with [`next`](Self::next) only if it lands after the current element. The
+    /// position of the cursor is shifted when the element lands before it.
+    ///
+    /// Returns whether the element was newly inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`from_pairs`](SortedGroups::from_pairs).
+    pub fn insert_in_order(&mut self, element: E) -> bool {
+        let group = self
+            .sorted_groups
+            .group_of(&element)
+            .expect("SortedGroups has no grouping function, use insert_with_group");
+        let group_index = self.sorted_groups.group_index_or_insert(group);
+        let Some(rank) = self.sorted_groups.insert_in_group_at(group_index, element) else {
+            self.sorted_groups.drop_group_if_empty(group_index);
+            return false;
+        };
+        // Past the last element stays past it, otherwise follow the current element
+        let position = self.sorted_groups.offset(group_index) + rank;
+        if position <= self.index {
+            self.index += 1;
+        }
+        true
+    }
+}
+
+impl<G, E, S> fmt::Debug for CursorMut<'_, G, E, S>
+where
+    G: Ord + fmt::Debug,
+    E: fmt::Debug,
+    S: GroupStorage<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_cursor().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_cursor() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        let mut cursor = sorted_groups.cursor_at(1);
+
+        assert_eq!(cursor.current(), Some((&1, &12)));
+        assert_eq!(cursor.next(), Some((&2, &21)));
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.prev(), Some((&2, &21)));
+        assert_eq!(cursor.prev(), Some((&1, &12)));
+        assert_eq!(cursor.prev(), Some((&1, &11)));
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.position(), 0);
+    }
+
+    #[test]
+    fn test_cursor_seek_group() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 31], |e| e / 10);
+        sorted_groups.add_group(2);
+        let mut cursor = sorted_groups.cursor_at(10);
+
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(cursor.seek_group(&2), Some((&3, &31)));
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.seek_group(&1), Some((&1, &11)));
+        assert_eq!(cursor.seek_group(&4), None);
+        assert_eq!(cursor.position(), 3);
+    }
+
+    #[test]
+    fn test_cursor_seek_descending_group() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 21, 31], |e| e / 10).reverse_groups();
+        let mut cursor = sorted_groups.cursor_at(0);
+
+        assert_eq!(cursor.seek_group(&2), Some((&2, &21)));
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(cursor.next(), Some((&1, &11)));
+    }
+
+    #[test]
+    fn test_cursor_mut() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 13, 21, 31], |e| e / 10);
+        let mut cursor = sorted_groups.cursor_mut();
+        assert_eq!(cursor.next(), Some((&1, &13)));
+
+        assert!(cursor.insert_in_order(12));
+        assert!(!cursor.insert_in_order(11));
+        assert_eq!(cursor.current(), Some((&1, &13)));
+        assert_eq!(cursor.position(), 2);
+        assert!(cursor.insert_in_order(14));
+        assert_eq!(cursor.next(), Some((&1, &14)));
+        assert_eq!(cursor.next(), Some((&2, &21)));
+        assert_eq!(cursor.remove_current(), Some(21));
+        assert_eq!(cursor.current(), Some((&3, &31)));
+        assert_eq!(cursor.remove_current(), Some(31));
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(cursor.prev(), Some((&1, &14)));
+
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(sorted_groups.get(3), Some((&1, &14)));
+    }
+}
@@ -0,0 +1,363 @@
+//! Change sets between two instances, see [`SortedGroups::diff`] and [`SortedGroups::apply`].
+use std::{error::Error, fmt, ops::Bound};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::SortedGroups;
+
+/// Change turning a [`SortedGroups`] into another, see [`SortedGroups::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Change<G, E> {
+    /// `group` was inserted, without elements, at the position `index` among groups
+    GroupInserted { group: G, index: usize },
+    /// `group`, which has no elements left, was removed from the position `index` among groups
+    GroupRemoved { group: G, index: usize },
+    /// `element` was inserted in `group` at the flat `index`
+    ElementInserted { group: G, element: E, index: usize },
+    /// `element` was removed from `group` at the flat `index`
+    ElementRemoved { group: G, element: E, index: usize },
+}
+
+impl<G, E> Change<G, E> {
+    /// Borrows the group and element of the change, as notified to an observer.
+    pub fn as_ref(&self) -> Change<&G, &E> {
+        match self {
+            Change::GroupInserted { group, index } => Change::GroupInserted {
+                group,
+                index: *index,
+            },
+            Change::GroupRemoved { group, index } => Change::GroupRemoved {
+                group,
+                index: *index,
+            },
+            Change::ElementInserted {
+                group,
+                element,
+                index,
+            } => Change::ElementInserted {
+                group,
+                element,
+                index: *index,
+            },
+            Change::ElementRemoved {
+                group,
+                element,
+                index,
+            } => Change::ElementRemoved {
+                group,
+                element,
+                index: *index,
+            },
+        }
+    }
+}
+
+impl<G, E> Change<&G, &E>
+where
+    G: Clone,
+    E: Clone,
+{
+    /// Clones the group and element of a borrowed change, as notified to an observer, see
+    /// [`SortedGroups::set_observer`].
+    pub fn cloned(&self) -> Change<G, E> {
+        match *self {
+            Change::GroupInserted { group, index } => Change::GroupInserted {
+                group: group.clone(),
+                index,
+            },
+            Change::GroupRemoved { group, index } => Change::GroupRemoved {
+                group: group.clone(),
+                index,
+            },
+            Change::ElementInserted {
+                group,
+                element,
+                index,
+            } => Change::ElementInserted {
+                group: group.clone(),
+                element: element.clone(),
+                index,
+            },
+            Change::ElementRemoved {
+                group,
+                element,
+                index,
+            } => Change::ElementRemoved {
+                group: group.clone(),
+                element: element.clone(),
+                index,
+            },
+        }
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Returns the changes turning `self` into `other`.
+    ///
+    /// Removals come first, in descending order of their indices in `self`, followed by
+    /// insertions in ascending order of their indices in `other`. Changes can thus be applied one
+    /// after the other, as well as in a single batch where removal indices refer to `self` and
+    /// insertion indices to `other`. The elements of a removed group are removed before the group,
+    /// those of an inserted group are inserted after the group. Indices follow the order of
+    /// groups of each instance, which should thus both have descending groups or neither.
+    pub fn diff(&self, other: &Self) -> Vec<Change<G, E>> {
+        let mut changes = Vec::new();
+
+        for (group_index, (group, elements)) in self.groups.iter().enumerate().rev() {
+            let other_elements = other.group_elements(group);
+            let removed = elements.iter().enumerate().rev().filter(|(_, element)| {
+                !other_elements.is_some_and(|other_elements| other_elements.contains(*element))
+            });
+            for (rank, element) in removed {
+                changes.push(Change::ElementRemoved {
+                    group: group.clone(),
+                    element: element.clone(),
+                    index: self.offset(group_index) + rank,
+                });
+            }
+            if other_elements.is_none() {
+                changes.push(Change::GroupRemoved {
+                    group: group.clone(),
+                    index: group_index,
+                });
+            }
+        }
+
+        for (group_index, (group, other_elements)) in other.groups.iter().enumerate() {
+            let elements = self.group_elements(group);
+            if elements.is_none() {
+                changes.push(Change::GroupInserted {
+                    group: group.clone(),
+                    index: group_index,
+                });
+            }
+            let inserted = other_elements.iter().enumerate().filter(|(_, element)| {
+                !elements.is_some_and(|elements| elements.contains(*element))
+            });
+            for (rank, element) in inserted {
+                changes.push(Change::ElementInserted {
+                    group: group.clone(),
+                    element: element.clone(),
+                    index: other.offset(group_index) + rank,
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+/// Error returned by [`SortedGroups::apply`] when a change doesn't match the current state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApplyError {
+    /// The inserted group already exists
+    GroupExists,
+    /// The group of the change doesn't exist
+    GroupNotFound,
+    /// The removed group still has elements
+    GroupNotEmpty,
+    /// The inserted element already exists
+    ElementExists,
+    /// The removed element doesn't exist
+    ElementNotFound,
+    /// The index of the change is not the position of its group or element
+    IndexMismatch { index: usize, position: usize },
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApplyError::GroupExists => f.write_str("group already exists"),
+            ApplyError::GroupNotFound => f.write_str("group doesn't exist"),
+            ApplyError::GroupNotEmpty => f.write_str("group is not empty"),
+            ApplyError::ElementExists => f.write_str("element already exists"),
+            ApplyError::ElementNotFound => f.write_str("element doesn't exist"),
+            ApplyError::IndexMismatch { index, position } => {
+                write!(f, "index {index} doesn't match position {position}")
+            }
+        }
+    }
+}
+
+impl Error for ApplyError {}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Applies `changes` one after the other, as returned by [`diff`](Self::diff).
+    ///
+    /// Groups are neither created nor dropped implicitly, only by [`Change::GroupInserted`] and
+    /// [`Change::GroupRemoved`]. Applying stops at the first change that doesn't match the current
+    /// state, changes applied until then are kept.
+    pub fn apply(
+        &mut self,
+        changes: impl IntoIterator<Item = Change<G, E>>,
+    ) -> Result<(), ApplyError> {
+        for change in changes {
+            match change {
+                Change::GroupInserted { group, index } => {
+                    let Err(group_index) = self.group_index(&group) else {
+                        return Err(ApplyError::GroupExists);
+                    };
+                    check_index(index, group_index)?;
+                    self.insert_group(group_index, group);
+                }
+                Change::GroupRemoved { group, index } => {
+                    let group_index = self.existing_group_index(&group)?;
+                    check_index(index, group_index)?;
+                    if !self.groups[group_index].1.is_empty() {
+                        return Err(ApplyError::GroupNotEmpty);
+                    }
+                    self.remove_group_at(group_index);
+                }
+                Change::ElementInserted {
+                    group,
+                    element,
+                    index,
+                } => {
+                    let group_index = self.existing_group_index(&group)?;
+                    let elements = &self.groups[group_index].1;
+                    if elements.contains(&element) {
+                        return Err(ApplyError::ElementExists);
+                    }
+                    let rank = elements
+                        .range::<E, _>((Bound::Unbounded, Bound::Excluded(&element)))
+                        .count();
+                    check_index(index, self.offset(group_index) + rank)?;
+                    self.debug_place_at(group_index, &element);
+                    self.insert_in_group(group_index, element);
+                }
+                Change::ElementRemoved {
+                    group,
+                    element,
+                    index,
+                } => {
+                    let group_index = self.existing_group_index(&group)?;
+                    let position = self
+                        .position_of(&group, &element)
+                        .ok_or(ApplyError::ElementNotFound)?;
+                    check_index(index, position)?;
+                    self.take_from_group(group_index, &element);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn existing_group_index(&self, group: &G) -> Result<usize, ApplyError> {
+        self.group_index(group)
+            .map_err(|_| ApplyError::GroupNotFound)
+    }
+}
+
+fn check_index(index: usize, position: usize) -> Result<(), ApplyError> {
+    if index != position {
+        return Err(ApplyError::IndexMismatch { index, position });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 22], |e| e / 10);
+        let new = SortedGroups::<i32, i32>::new(vec![12, 13, 31], |e| e / 10);
+
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                Change::ElementRemoved {
+                    group: 2,
+                    element: 22,
+                    index: 3
+                },
+                Change::ElementRemoved {
+                    group: 2,
+                    element: 21,
+                    index: 2
+                },
+                Change::GroupRemoved { group: 2, index: 1 },
+                Change::ElementRemoved {
+                    group: 1,
+                    element: 11,
+                    index: 0
+                },
+                Change::ElementInserted {
+                    group: 1,
+                    element: 13,
+                    index: 1
+                },
+                Change::GroupInserted { group: 3, index: 1 },
+                Change::ElementInserted {
+                    group: 3,
+                    element: 31,
+                    index: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        assert_eq!(sorted_groups.diff(&sorted_groups.clone()), vec![]);
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let mut old = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 22, 41], |e| e / 10);
+        let mut new = SortedGroups::<i32, i32>::new(vec![12, 13, 31, 41, 42], |e| e / 10);
+        new.add_group(5);
+
+        let changes = old.diff(&new);
+        old.apply(changes).unwrap();
+        assert_eq!(old, new);
+        assert_eq!(old.len(), 5);
+        assert_eq!(old.get(4), Some((&4, &42)));
+    }
+
+    #[test]
+    fn test_apply_rejects_mismatching_changes() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.apply([Change::GroupInserted { group: 1, index: 0 }]),
+            Err(ApplyError::GroupExists)
+        );
+        assert_eq!(
+            sorted_groups.apply([Change::GroupRemoved { group: 1, index: 0 }]),
+            Err(ApplyError::GroupNotEmpty)
+        );
+        assert_eq!(
+            sorted_groups.apply([Change::ElementInserted {
+                group: 2,
+                element: 21,
+                index: 2
+            }]),
+            Err(ApplyError::GroupNotFound)
+        );
+        assert_eq!(
+            sorted_groups.apply([Change::ElementRemoved {
+                group: 1,
+                element: 12,
+                index: 0
+            }]),
+            Err(ApplyError::IndexMismatch {
+                index: 0,
+                position: 1
+            })
+        );
+        assert_eq!(sorted_groups.len(), 2);
+    }
+}
@@ -0,0 +1,132 @@
+//! Textual layout of groups and elements, see [`SortedGroups::display`].
+use std::{collections::BTreeSet, fmt};
+
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns a value implementing `Display` with one line per group followed by one indented
+    /// line per element, whose layout can be configured.
+    pub fn display(&self) -> DisplayGroups<'_, G, E, S> {
+        DisplayGroups {
+            sorted_groups: self,
+            indent: "  ",
+            skip_empty_groups: false,
+        }
+    }
+}
+
+/// Configurable layout of a [`SortedGroups`], see [`SortedGroups::display`].
+pub struct DisplayGroups<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a SortedGroups<G, E, S>,
+    // Written before each element
+    indent: &'a str,
+    skip_empty_groups: bool,
+}
+
+impl<'a, G, E, S> DisplayGroups<'a, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Sets the prefix written before each element, two spaces by default.
+    pub fn indent(mut self, indent: &'a str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets whether groups without elements are left out, `false` by default.
+    pub fn skip_empty_groups(mut self, skip_empty_groups: bool) -> Self {
+        self.skip_empty_groups = skip_empty_groups;
+        self
+    }
+
+    // Writes each group followed by a colon and its elements, on separate lines
+    pub(crate) fn write_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        write_group: fn(&G, &mut fmt::Formatter<'_>) -> fmt::Result,
+        write_element: fn(&E, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        let mut first = true;
+        for (group, elements) in self.sorted_groups.iter_groups() {
+            if self.skip_empty_groups && elements.is_empty() {
+                continue;
+            }
+            if !std::mem::take(&mut first) {
+                f.write_str("\n")?;
+            }
+            write_group(group, f)?;
+            f.write_str(":")?;
+            for element in elements.iter() {
+                write!(f, "\n{}", self.indent)?;
+                write_element(element, f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<G, E, S> fmt::Display for DisplayGroups<'_, G, E, S>
+where
+    G: Ord + fmt::Display,
+    E: fmt::Display,
+    S: GroupStorage<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, G::fmt, E::fmt)
+    }
+}
+
+/// Writes one line per group followed by one indented line per element, see
+/// [`SortedGroups::display`] to configure the layout.
+impl<G, E, S> fmt::Display for SortedGroups<G, E, S>
+where
+    G: Ord + fmt::Display,
+    E: fmt::Display,
+    S: GroupStorage<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    fn tasks() -> SortedGroups<&'static str, &'static str> {
+        let mut tasks = SortedGroups::from_pairs([("today", "task a"), ("today", "task b")]);
+        tasks.add_group("later");
+        tasks
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(tasks().to_string(), "later:\ntoday:\n  task a\n  task b");
+        assert_eq!(
+            tasks()
+                .display()
+                .indent("- ")
+                .skip_empty_groups(true)
+                .to_string(),
+            "today:\n- task a\n- task b"
+        );
+    }
+
+    #[test]
+    fn test_alternate_debug() {
+        assert_eq!(
+            format!("{:#?}", tasks()),
+            "\"later\":\n\"today\":\n  \"task a\"\n  \"task b\""
+        );
+        assert!(format!("{:?}", tasks()).starts_with("SortedGroups {"));
+    }
+}
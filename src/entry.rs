@@ -0,0 +1,286 @@
+//! Entry API to access a group in a single lookup, see [`SortedGroups::entry`].
+use std::collections::BTreeSet;
+
+use crate::{GroupLookup, GroupStorage, SortedGroups};
+
+/// A view into a single group, which may be vacant or occupied.
+///
+/// This is constructed from [`SortedGroups::entry`].
+pub enum GroupEntry<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    Occupied(OccupiedGroupEntry<'a, G, E, S>),
+    Vacant(VacantGroupEntry<'a, G, E, S>),
+}
+
+impl<'a, G, E, S> GroupEntry<'a, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    pub(crate) fn new(sorted_groups: &'a mut SortedGroups<G, E, S>, group: G) -> Self {
+        match sorted_groups.group_index(&group) {
+            Ok(index) => GroupEntry::Occupied(OccupiedGroupEntry {
+                sorted_groups,
+                index,
+                drop_if_empty: false,
+            }),
+            Err(index) => GroupEntry::Vacant(VacantGroupEntry {
+                sorted_groups,
+                group,
+                index,
+            }),
+        }
+    }
+
+    /// Returns the group of this entry.
+    pub fn key(&self) -> &G {
+        match self {
+            GroupEntry::Occupied(entry) => entry.key(),
+            GroupEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Ensures the group exists, creating it empty if vacant.
+    pub fn or_default(self) -> OccupiedGroupEntry<'a, G, E, S> {
+        self.or_insert_with(std::iter::empty)
+    }
+
+    /// Ensures the group exists, creating it with the elements returned by `elements` if vacant.
+    pub fn or_insert_with<I>(self, elements: impl FnOnce() -> I) -> OccupiedGroupEntry<'a, G, E, S>
+    where
+        I: IntoIterator<Item = E>,
+    {
+        match self {
+            GroupEntry::Occupied(entry) => entry,
+            GroupEntry::Vacant(entry) => entry.insert_elements(elements()),
+        }
+    }
+}
+
+/// A view into an existing group.
+///
+/// Elements inserted or removed through this view keep the [`SortedGroups`] consistent. If the
+/// group was created or had elements removed through this view, it is dropped along with the view
+/// when left empty, unless empty groups are kept.
+pub struct OccupiedGroupEntry<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a mut SortedGroups<G, E, S>,
+    index: usize,
+    // Whether the group should be dropped along with the view if empty
+    drop_if_empty: bool,
+}
+
+impl<G, E, S> OccupiedGroupEntry<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns the group of this entry.
+    pub fn key(&self) -> &G {
+        &self.sorted_groups.groups[self.index].0
+    }
+
+    /// Returns the number of elements in the group.
+    pub fn len(&self) -> usize {
+        self.elements().len()
+    }
+
+    /// Returns whether the group has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements().is_empty()
+    }
+
+    /// Returns whether the group contains `element`.
+    pub fn contains<R>(&self, element: &R) -> bool
+    where
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.elements().contains(element)
+    }
+
+    /// Returns an iterator over the elements of the group.
+    pub fn iter(&self) -> impl Iterator<Item = &E> {
+        self.elements().iter()
+    }
+
+    /// Inserts `element` in the group.
+    ///
+    /// Returns whether the element was newly inserted.
+    pub fn insert(&mut self, element: E) -> bool {
+        self.sorted_groups.debug_place_at(self.index, &element);
+        self.sorted_groups.insert_in_group(self.index, element)
+    }
+
+    /// Removes `element` from the group.
+    ///
+    /// Returns whether the element was present.
+    pub fn remove<R>(&mut self, element: &R) -> bool
+    where
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.take(element).is_some()
+    }
+
+    /// Removes and returns the element equal to `element` from the group.
+    pub fn take<R>(&mut self, element: &R) -> Option<E>
+    where
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        let element = self.sorted_groups.take_from_group(self.index, element)?;
+        self.drop_if_empty = true;
+        Some(element)
+    }
+
+    fn elements(&self) -> &S {
+        &self.sorted_groups.groups[self.index].1
+    }
+}
+
+impl<G, E, S> Drop for OccupiedGroupEntry<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn drop(&mut self) {
+        if self.drop_if_empty {
+            self.sorted_groups.drop_group_if_empty(self.index);
+        }
+    }
+}
+
+/// A view into a group that doesn't exist yet.
+pub struct VacantGroupEntry<'a, G, E, S = BTreeSet<E>>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a mut SortedGroups<G, E, S>,
+    group: G,
+    // Position of the group once inserted
+    index: usize,
+}
+
+impl<'a, G, E, S> VacantGroupEntry<'a, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns the group of this entry.
+    pub fn key(&self) -> &G {
+        &self.group
+    }
+
+    /// Takes ownership of the group.
+    pub fn into_key(self) -> G {
+        self.group
+    }
+
+    /// Creates the group with `element` as its first element.
+    pub fn insert(self, element: E) -> OccupiedGroupEntry<'a, G, E, S> {
+        self.insert_elements([element])
+    }
+
+    fn insert_elements(
+        self,
+        elements: impl IntoIterator<Item = E>,
+    ) -> OccupiedGroupEntry<'a, G, E, S> {
+        self.sorted_groups.insert_group(self.index, self.group);
+        for element in elements {
+            self.sorted_groups.debug_place_at(self.index, &element);
+            self.sorted_groups.insert_in_group(self.index, element);
+        }
+        OccupiedGroupEntry {
+            sorted_groups: self.sorted_groups,
+            index: self.index,
+            drop_if_empty: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_or_default() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+
+        let mut entry = sorted_groups.entry(1).or_default();
+        assert!(entry.insert(12));
+        assert!(!entry.insert(11));
+        assert_eq!(entry.len(), 2);
+        drop(entry);
+
+        let mut entry = sorted_groups.entry(3).or_default();
+        assert_eq!(entry.key(), &3);
+        assert!(entry.insert(30));
+        drop(entry);
+
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12), (&2, &21), (&3, &30)]
+        );
+    }
+
+    #[test]
+    fn test_entry_or_insert_with() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+
+        let entry = sorted_groups.entry(2).or_insert_with(|| [22, 21]);
+        assert_eq!(entry.iter().collect::<Vec<_>>(), vec![&21, &22]);
+        drop(entry);
+        let entry = sorted_groups.entry(1).or_insert_with(|| [13]);
+        assert!(!entry.contains(&13));
+        drop(entry);
+
+        assert_eq!(sorted_groups.len(), 3);
+        assert_eq!(sorted_groups.get(2), Some((&2, &22)));
+    }
+
+    #[test]
+    fn test_entry_removing_all_elements_removes_group() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+
+        match sorted_groups.entry(1) {
+            GroupEntry::Occupied(mut entry) => {
+                assert_eq!(entry.take(&11), Some(11));
+                assert!(entry.remove(&12));
+                assert!(!entry.remove(&12));
+            }
+            GroupEntry::Vacant(_) => panic!("group 1 should exist"),
+        }
+        drop(sorted_groups.entry(3).or_default());
+
+        assert_eq!(sorted_groups.len(), 1);
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(sorted_groups.get(0), Some((&2, &21)));
+    }
+
+    #[test]
+    fn test_vacant_entry() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+
+        match sorted_groups.entry(0) {
+            GroupEntry::Vacant(entry) => {
+                assert_eq!(entry.key(), &0);
+                entry.insert(1);
+            }
+            GroupEntry::Occupied(_) => panic!("group 0 should not exist"),
+        }
+
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&0, &1), (&1, &11)]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Read access to the elements of a group, see [`Group`].
+use std::{
+    borrow::Borrow,
+    collections::BTreeSet,
+    fmt,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{GroupLookup, GroupStorage, SortedVec};
+
+/// Elements of a group, as returned by [`SortedGroups::iter_groups`](crate::SortedGroups::iter_groups),
+/// hiding the storage holding them.
+pub struct Group<'a, E, S = BTreeSet<E>> {
+    elements: &'a S,
+    _elements: PhantomData<&'a E>,
+}
+
+impl<'a, E, S> Group<'a, E, S>
+where
+    S: GroupStorage<E>,
+{
+    pub(crate) fn new(elements: &'a S) -> Self {
+        Self {
+            elements,
+            _elements: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether the group has no elements, which only happens for groups kept while empty.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns an iterator over the elements, in iteration order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a E> {
+        self.into_iter()
+    }
+
+    /// Returns the first element in iteration order.
+    pub fn first(&self) -> Option<&'a E> {
+        self.elements.iter().next()
+    }
+
+    /// Returns the last element in iteration order.
+    pub fn last(&self) -> Option<&'a E> {
+        self.elements.iter().next_back()
+    }
+
+    /// Returns whether the group contains `element`.
+    pub fn contains<R>(&self, element: &R) -> bool
+    where
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.elements.contains(element)
+    }
+}
+
+impl<'a, E: Ord> Group<'a, E> {
+    /// Returns an iterator over the elements within `range`.
+    ///
+    /// Ranges are available for sorted storages, `BTreeSet` and [`SortedVec`]. Inverted or empty
+    /// ranges yield no elements.
+    pub fn range<R, B>(&self, range: B) -> impl DoubleEndedIterator<Item = &'a E>
+    where
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+        B: RangeBounds<R>,
+    {
+        let bounds: (Bound<&R>, Bound<&R>) = (range.start_bound(), range.end_bound());
+        // `BTreeSet::range` panics on these ranges instead of returning nothing
+        let empty = match bounds {
+            (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => start > end,
+            _ => false,
+        };
+        (!empty)
+            .then(|| self.elements.range::<R, _>(bounds))
+            .into_iter()
+            .flatten()
+    }
+}
+
+impl<'a, E: Ord> Group<'a, E, SortedVec<E>> {
+    /// Returns an iterator over the elements within `range`.
+    ///
+    /// Ranges are available for sorted storages, `BTreeSet` and [`SortedVec`].
+    pub fn range<R, B>(&self, range: B) -> impl DoubleEndedIterator<Item = &'a E>
+    where
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+        B: RangeBounds<R>,
+    {
+        let elements = self.elements.as_slice();
+        let start = elements.partition_point(|e| match range.start_bound() {
+            Bound::Included(start) => e.borrow() < start,
+            Bound::Excluded(start) => e.borrow() <= start,
+            Bound::Unbounded => false,
+        });
+        let end = elements.partition_point(|e| match range.end_bound() {
+            Bound::Included(end) => e.borrow() <= end,
+            Bound::Excluded(end) => e.borrow() < end,
+            Bound::Unbounded => true,
+        });
+        elements[start..end.max(start)].iter()
+    }
+}
+
+impl<E, S> Clone for Group<'_, E, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E, S> Copy for Group<'_, E, S> {}
+
+impl<'a, E, S> IntoIterator for Group<'a, E, S>
+where
+    S: GroupStorage<E>,
+{
+    type Item = &'a E;
+    type IntoIter = GroupIter<'a, E, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        GroupIter::<E, S>(self.elements.iter())
+    }
+}
+
+/// Iterator over the elements of a [`Group`], in iteration order.
+pub struct GroupIter<'a, E, S>(S::Iter<'a>)
+where
+    E: 'a,
+    S: GroupStorage<E> + 'a;
+
+impl<'a, E, S> Iterator for GroupIter<'a, E, S>
+where
+    S: GroupStorage<E>,
+{
+    type Item = &'a E;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<E, S> DoubleEndedIterator for GroupIter<'_, E, S>
+where
+    S: GroupStorage<E>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<E, S> PartialEq for Group<'_, E, S>
+where
+    E: PartialEq,
+    S: GroupStorage<E>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<E, S> Eq for Group<'_, E, S>
+where
+    E: Eq,
+    S: GroupStorage<E>,
+{
+}
+
+impl<E, S> fmt::Debug for Group<'_, E, S>
+where
+    E: fmt::Debug,
+    S: GroupStorage<E>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SortedGroups, SortedVec};
+
+    #[test]
+    fn test_group() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21], |e| e / 10);
+        let (_, group) = sorted_groups.first_group().unwrap();
+
+        assert_eq!(group.len(), 3);
+        assert!(group.contains(&12));
+        assert_eq!((group.first(), group.last()), (Some(&11), Some(&13)));
+        assert_eq!(group.range(12..).collect::<Vec<_>>(), vec![&12, &13]);
+        assert_eq!(group.into_iter().next_back(), Some(&13));
+        assert_eq!(format!("{group:?}"), "[11, 12, 13]");
+    }
+
+    #[test]
+    fn test_sorted_vec_group_range() {
+        let sorted_groups = SortedGroups::<i32, i32, SortedVec<i32>>::new_with_storage(
+            vec![11, 12, 12, 13, 21],
+            |e| e / 10,
+        );
+        let (_, group) = sorted_groups.first_group().unwrap();
+
+        assert_eq!(group.range(12..).collect::<Vec<_>>(), vec![&12, &12, &13]);
+        assert_eq!(group.range(..=12).count(), 3);
+        assert_eq!(group.range(14..).count(), 0);
+    }
+}
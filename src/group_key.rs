@@ -0,0 +1,73 @@
+//! Grouping declared by element types, see [`GroupKey`].
+use crate::{GroupStorage, SortedGroups};
+
+/// Element type declaring its own group, to build a [`SortedGroups`] without passing a grouping
+/// function, see [`SortedGroups::from_elements`].
+pub trait GroupKey {
+    type Group: Ord;
+
+    fn group(&self) -> Self::Group;
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord + GroupKey<Group = G> + 'static,
+{
+    /// Creates a new `SortedGroups` from `elements`, grouped by [`GroupKey::group`].
+    ///
+    /// The grouping is kept for elements inserted later on, as with [`new`](Self::new).
+    pub fn from_elements(elements: impl IntoIterator<Item = E>) -> Self {
+        Self::new(elements, E::group)
+    }
+}
+
+/// Groups elements by [`GroupKey::group`], see [`SortedGroups::from_elements`].
+impl<G, E, S> FromIterator<E> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    E: GroupKey<Group = G> + 'static,
+    S: GroupStorage<E>,
+{
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        Self::new_with_storage(iter, E::group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Task {
+        day: u32,
+        title: &'static str,
+    }
+
+    impl GroupKey for Task {
+        type Group = u32;
+
+        fn group(&self) -> u32 {
+            self.day
+        }
+    }
+
+    #[test]
+    fn test_group_key() {
+        let mut sorted_groups: SortedGroups<u32, Task> =
+            [Task { day: 2, title: "b" }, Task { day: 1, title: "a" }]
+                .into_iter()
+                .collect();
+        sorted_groups.extend([Task { day: 2, title: "a" }]);
+
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(
+            sorted_groups.get(1),
+            Some((&2, &Task { day: 2, title: "a" }))
+        );
+        assert_eq!(
+            sorted_groups,
+            SortedGroups::from_elements(sorted_groups.elements().map(|task| Task { ..*task }))
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Undo and redo of changes, see [`SortedGroups::enable_history`].
+use crate::{Change, SortedGroups};
+
+/// Marker of a version to go back to with [`SortedGroups::restore`], returned by
+/// [`SortedGroups::snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    id: u64,
+}
+
+// Log of the changes made since history was enabled
+pub(crate) struct History<G, E> {
+    // Changes from the oldest one, those from `position` were undone and can be redone
+    changes: Vec<Change<G, E>>,
+    position: usize,
+    // Identifier and position in `changes` of each snapshot, sorted by position
+    snapshots: Vec<(u64, usize)>,
+    next_id: u64,
+    // Captures `G: Clone` and `E: Clone` when history is enabled, so that changes can be recorded
+    // from any mutation
+    clone_change: fn(&Change<&G, &E>) -> Change<G, E>,
+}
+
+impl<G, E> History<G, E> {
+    // Records `change`, dropping the changes that were undone along with their snapshots
+    pub(crate) fn record(&mut self, change: &Change<&G, &E>) {
+        self.changes.truncate(self.position);
+        let position = self.position;
+        self.snapshots
+            .retain(|&(_, snapshot_position)| snapshot_position <= position);
+        self.changes.push((self.clone_change)(change));
+        self.position += 1;
+    }
+
+    // Records `changes` made together as a single undo step, delimited by snapshots
+    pub(crate) fn record_batch(&mut self, changes: &[Change<&G, &E>]) {
+        if changes.is_empty() {
+            return;
+        }
+        self.mark();
+        changes.iter().for_each(|change| self.record(change));
+        self.mark();
+    }
+
+    // Marks the current version with a new snapshot
+    fn mark(&mut self) -> Snapshot {
+        let id = self.next_id;
+        self.next_id += 1;
+        let position = self.position;
+        let index = self
+            .snapshots
+            .partition_point(|&(_, snapshot_position)| snapshot_position <= position);
+        self.snapshots.insert(index, (id, position));
+        Snapshot { id }
+    }
+
+    // Changes until the current version
+    pub(crate) fn recorded(&self) -> &[Change<G, E>] {
+        &self.changes[..self.position]
+    }
+
+    // Takes the changes until the current version, dropping the undone ones and all snapshots
+    pub(crate) fn take_recorded(&mut self) -> Vec<Change<G, E>> {
+        self.changes.truncate(self.position);
+        self.position = 0;
+        self.snapshots.clear();
+        std::mem::take(&mut self.changes)
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Starts recording changes, so that they can be undone and redone, replacing the previous
+    /// history if any.
+    ///
+    /// Every change is kept until history is disabled, see [`snapshot`](Self::snapshot) to
+    /// delimit undo steps. History is not cloned along with the `SortedGroups`.
+    pub fn enable_history(&mut self) {
+        self.history = Some(History {
+            changes: Vec::new(),
+            position: 0,
+            snapshots: Vec::new(),
+            next_id: 0,
+            clone_change: |change| change.cloned(),
+        });
+    }
+
+    /// Stops recording changes, dropping the recorded ones.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Marks the current version, delimiting the changes reverted by [`undo`](Self::undo) and
+    /// reapplied by [`redo`](Self::redo).
+    ///
+    /// The snapshot stays valid until the changes made before it are undone and new changes are
+    /// made.
+    ///
+    /// # Panics
+    ///
+    /// Panics if history is not enabled, see [`enable_history`](Self::enable_history).
+    pub fn snapshot(&mut self) -> Snapshot {
+        self.history
+            .as_mut()
+            .expect("SortedGroups has no history, use enable_history")
+            .mark()
+    }
+
+    /// Reverts the changes made since the previous snapshot, or since history was enabled if
+    /// there is none, returning whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(history) = &self.history else {
+            return false;
+        };
+        let position = history.position;
+        let target = history
+            .snapshots
+            .iter()
+            .rev()
+            .map(|&(_, snapshot_position)| snapshot_position)
+            .find(|&snapshot_position| snapshot_position < position)
+            .unwrap_or(0);
+        self.move_to(target)
+    }
+
+    /// Reapplies the changes reverted by [`undo`](Self::undo) up to the next snapshot, returning
+    /// whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(history) = &self.history else {
+            return false;
+        };
+        let position = history.position;
+        let target = history
+            .snapshots
+            .iter()
+            .map(|&(_, snapshot_position)| snapshot_position)
+            .find(|&snapshot_position| snapshot_position > position)
+            .unwrap_or(history.changes.len());
+        self.move_to(target)
+    }
+
+    /// Goes back or forth to the version marked by `snapshot`, returning whether the snapshot is
+    /// still valid.
+    pub fn restore(&mut self, snapshot: Snapshot) -> bool {
+        let Some(history) = &self.history else {
+            return false;
+        };
+        let Some(&(_, position)) = history.snapshots.iter().find(|(id, _)| *id == snapshot.id)
+        else {
+            return false;
+        };
+        self.move_to(position);
+        true
+    }
+
+    // Reverts or reapplies recorded changes until `position`, returning whether any change was
+    // made
+    fn move_to(&mut self, target: usize) -> bool {
+        // History is taken out so that the changes made here are not recorded
+        let Some(mut history) = self.history.take() else {
+            return false;
+        };
+        let moved = target != history.position;
+        let changes = if target < history.position {
+            history.changes[target..history.position]
+                .iter()
+                .rev()
+                .map(inverse)
+                .collect::<Vec<_>>()
+        } else {
+            history.changes[history.position..target].to_vec()
+        };
+        self.apply(changes)
+            .expect("recorded changes match the current state");
+        history.position = target;
+        self.history = Some(history);
+        moved
+    }
+}
+
+// Change reverting `change`
+pub(crate) fn inverse<G: Clone, E: Clone>(change: &Change<G, E>) -> Change<G, E> {
+    match change.clone() {
+        Change::GroupInserted { group, index } => Change::GroupRemoved { group, index },
+        Change::GroupRemoved { group, index } => Change::GroupInserted { group, index },
+        Change::ElementInserted {
+            group,
+            element,
+            index,
+        } => Change::ElementRemoved {
+            group,
+            element,
+            index,
+        },
+        Change::ElementRemoved {
+            group,
+            element,
+            index,
+        } => Change::ElementInserted {
+            group,
+            element,
+            index,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    fn elements(sorted_groups: &SortedGroups<i32, i32>) -> Vec<i32> {
+        sorted_groups.iter().map(|(_, element)| *element).collect()
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        sorted_groups.enable_history();
+        let initial = sorted_groups.snapshot();
+
+        sorted_groups.insert(12);
+        sorted_groups.insert(31);
+        sorted_groups.snapshot();
+        sorted_groups.remove(&2, &21);
+        sorted_groups.update(&1, &11, |e| e + 2);
+        assert_eq!(elements(&sorted_groups), vec![12, 13, 31]);
+
+        assert!(sorted_groups.undo());
+        assert_eq!(elements(&sorted_groups), vec![11, 12, 21, 31]);
+        assert!(sorted_groups.undo());
+        assert_eq!(elements(&sorted_groups), vec![11, 21]);
+        assert!(!sorted_groups.undo());
+
+        assert!(sorted_groups.redo());
+        assert!(sorted_groups.redo());
+        assert_eq!(elements(&sorted_groups), vec![12, 13, 31]);
+        assert!(!sorted_groups.redo());
+
+        assert!(sorted_groups.restore(initial));
+        assert_eq!(elements(&sorted_groups), vec![11, 21]);
+        assert_eq!(sorted_groups.groups_len(), 2);
+    }
+
+    #[test]
+    fn test_new_changes_drop_undone_ones() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        sorted_groups.enable_history();
+        sorted_groups.insert(12);
+        let snapshot = sorted_groups.snapshot();
+        sorted_groups.insert(13);
+
+        assert!(sorted_groups.restore(snapshot));
+        sorted_groups.undo();
+        sorted_groups.insert(21);
+        assert!(!sorted_groups.restore(snapshot));
+        assert!(!sorted_groups.redo());
+        assert!(sorted_groups.undo());
+        assert_eq!(elements(&sorted_groups), vec![11]);
+    }
+}
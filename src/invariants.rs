@@ -0,0 +1,233 @@
+//! Consistency checks of the internal state, see [`SortedGroups::check_invariants`].
+use std::{cmp::Ordering, error::Error, fmt};
+
+use crate::{GroupStorage, SortedGroups};
+
+/// Inconsistency found by [`SortedGroups::check_invariants`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// The group at `index` doesn't come strictly after the previous one, in the order of groups
+    GroupsNotSorted { index: usize },
+    /// The cached number of elements `len` doesn't match the `actual` number of elements
+    LenMismatch { len: usize, actual: usize },
+    /// The cached flat offset of the group at `index` doesn't match its `actual` offset
+    OffsetMismatch {
+        index: usize,
+        offset: usize,
+        actual: usize,
+    },
+    /// The element at the flat `index` isn't in the group given by the grouping function
+    GroupMismatch { index: usize },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::GroupsNotSorted { index } => {
+                write!(f, "group {index} is not sorted after the previous group")
+            }
+            InvariantViolation::LenMismatch { len, actual } => {
+                write!(f, "length {len} doesn't match the {actual} elements")
+            }
+            InvariantViolation::OffsetMismatch {
+                index,
+                offset,
+                actual,
+            } => write!(
+                f,
+                "offset {offset} of group {index} doesn't match its position {actual}"
+            ),
+            InvariantViolation::GroupMismatch { index } => {
+                write!(
+                    f,
+                    "element {index} is not in the group of the grouping function"
+                )
+            }
+        }
+    }
+}
+
+impl Error for InvariantViolation {}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Checks that the cached length and offsets match the elements, that groups are sorted, and
+    /// that every element is in the group given by the grouping function if any.
+    ///
+    /// Elements inserted with [`insert_with_group`](Self::insert_with_group) or moved with
+    /// [`move_element`](Self::move_element) to another group than their own are reported as
+    /// [`InvariantViolation::GroupMismatch`].
+    ///
+    /// Empty groups are not reported. Whether a group was added explicitly, with
+    /// [`add_group`](Self::add_group) or deserialized as such, isn't tracked, so an empty group
+    /// left behind by a removal can't be told apart from an added one.
+    ///
+    /// With the `strict` feature, these checks are debug asserted after every mutation. Grouping
+    /// is no longer asserted once elements are placed in another group than their own, as with
+    /// `insert_with_group`, since that is not a violation of the other invariants.
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        self.check_structure()?;
+        self.check_grouping()
+    }
+
+    // Checks that every element is in the group given by the grouping function if any
+    fn check_grouping(&self) -> Result<(), InvariantViolation> {
+        let Some(group_from_element) = &self.group_from_element else {
+            return Ok(());
+        };
+        match self
+            .iter()
+            .position(|(group, element)| group_from_element(element) != *group)
+        {
+            Some(index) => Err(InvariantViolation::GroupMismatch { index }),
+            None => Ok(()),
+        }
+    }
+
+    // Checks the cached length and offsets and the order of groups
+    fn check_structure(&self) -> Result<(), InvariantViolation> {
+        if let Some(index) = (1..self.groups.len()).find(|index| {
+            self.cmp_groups(&self.groups[index - 1].0, &self.groups[*index].0) != Ordering::Less
+        }) {
+            return Err(InvariantViolation::GroupsNotSorted { index });
+        }
+        let actual = self.groups.iter().map(|(_, elements)| elements.len()).sum();
+        if self.len != actual {
+            return Err(InvariantViolation::LenMismatch {
+                len: self.len,
+                actual,
+            });
+        }
+        // Offsets are only checked once computed, computing them here would hide stale ones
+        if let Some(offsets) = self.offsets.get() {
+            let mut actual = 0;
+            for (index, (_, elements)) in self.groups.iter().enumerate() {
+                let offset = offsets.offset(index);
+                if offset != actual {
+                    return Err(InvariantViolation::OffsetMismatch {
+                        index,
+                        offset,
+                        actual,
+                    });
+                }
+                actual += elements.len();
+            }
+        }
+        Ok(())
+    }
+
+    // Debug asserts the invariants after a mutation, with the `strict` feature, leaving the
+    // grouping of elements aside once some were placed in another group than their own
+    pub(crate) fn debug_check_invariants(&self) {
+        #[cfg(feature = "strict")]
+        {
+            debug_assert_eq!(self.check_structure(), Ok(()));
+            if !self.regrouped {
+                debug_assert_eq!(self.check_grouping(), Ok(()));
+            }
+        }
+    }
+
+    // Notes that every element is in the group of the grouping function, as after grouping them
+    pub(crate) fn debug_follow_grouping(&mut self) {
+        #[cfg(feature = "strict")]
+        {
+            self.regrouped = false;
+        }
+    }
+
+    // Notes that `element` is placed in `group` regardless of the grouping function
+    #[cfg_attr(not(feature = "strict"), allow(unused_variables))]
+    pub(crate) fn debug_place_in_group(&mut self, group: &G, element: &E) {
+        #[cfg(feature = "strict")]
+        if self.group_of(element).is_some_and(|own| own != *group) {
+            self.regrouped = true;
+        }
+    }
+
+    // Same as `debug_place_in_group` for the group at `index`
+    #[cfg_attr(not(feature = "strict"), allow(unused_variables))]
+    pub(crate) fn debug_place_at(&mut self, index: usize, element: &E) {
+        #[cfg(feature = "strict")]
+        if self
+            .group_of(element)
+            .is_some_and(|own| own != self.groups[index].0)
+        {
+            self.regrouped = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_invariants() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        sorted_groups.add_group(5);
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+
+        sorted_groups.get(2);
+        sorted_groups.remove(&1, &11);
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+
+        sorted_groups.insert_with_group(1, 35);
+        assert_eq!(
+            sorted_groups.check_invariants(),
+            Err(InvariantViolation::GroupMismatch { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_detects_corruption() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        sorted_groups.len = 3;
+        assert_eq!(
+            sorted_groups.check_invariants(),
+            Err(InvariantViolation::LenMismatch { len: 3, actual: 2 })
+        );
+
+        sorted_groups.len = 2;
+        sorted_groups.groups.swap(0, 1);
+        assert_eq!(
+            sorted_groups.check_invariants(),
+            Err(InvariantViolation::GroupsNotSorted { index: 1 })
+        );
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    #[should_panic(expected = "GroupMismatch")]
+    fn test_strict_asserts_grouping() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        sorted_groups.groups[0].1.insert(35);
+        sorted_groups.len += 1;
+        sorted_groups.insert(12);
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_strict_allows_explicit_groups() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        sorted_groups.insert_with_group(1, 35);
+        sorted_groups.merge_groups(&2, 1);
+        sorted_groups.insert(12);
+        assert_eq!(
+            sorted_groups.check_invariants(),
+            Err(InvariantViolation::GroupMismatch { index: 2 })
+        );
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    #[should_panic(expected = "LenMismatch")]
+    fn test_strict_asserts_structure() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        sorted_groups.len = 3;
+        sorted_groups.insert(12);
+    }
+}
@@ -0,0 +1,37 @@
+//! Collection of iterators into a [`SortedGroups`], see [`IntoSortedGroups`].
+use crate::SortedGroups;
+
+/// Extension trait collecting any iterator into a [`SortedGroups`].
+pub trait IntoSortedGroups: Iterator + Sized {
+    /// Collects elements into a [`SortedGroups`] grouped by `group_from_element`, see
+    /// [`SortedGroups::new`].
+    fn into_sorted_groups<G>(
+        self,
+        group_from_element: impl Fn(&Self::Item) -> G + Send + Sync + 'static,
+    ) -> SortedGroups<G, Self::Item>
+    where
+        G: Ord,
+        Self::Item: Ord,
+    {
+        SortedGroups::new(self, group_from_element)
+    }
+}
+
+impl<I: Iterator> IntoSortedGroups for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_sorted_groups() {
+        let sorted_groups = [31, 11, 12].into_iter().into_sorted_groups(|e| e / 10);
+
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12), (&3, &31)]
+        );
+        assert_eq!(sorted_groups.group_of(&21), Some(2));
+    }
+}
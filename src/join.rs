@@ -0,0 +1,222 @@
+//! Joins of two instances by group, see [`SortedGroups::join`], and merges of their elements.
+use std::{cmp::Ordering, iter::Peekable, slice};
+
+use crate::{Group, GroupStorage, Reversible, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns an iterator over the groups of both `self` and `other`, along with their elements
+    /// in each, in the order of the groups of `self`.
+    ///
+    /// As with other joins, `other` may order its groups the other way, see
+    /// [`descending_groups`](Self::descending_groups), its groups then being walked backwards.
+    pub fn join<'a, E2, S2>(
+        &'a self,
+        other: &'a SortedGroups<G, E2, S2>,
+    ) -> impl Iterator<Item = (&'a G, Group<'a, E, S>, Group<'a, E2, S2>)>
+    where
+        S2: GroupStorage<E2>,
+    {
+        self.outer_join(other)
+            .filter_map(|(group, elements, other_elements)| {
+                Some((group, elements?, other_elements?))
+            })
+    }
+
+    /// Returns an iterator over the groups of `self`, along with their elements in `self` and in
+    /// `other` if the group exists there.
+    pub fn left_join<'a, E2, S2>(
+        &'a self,
+        other: &'a SortedGroups<G, E2, S2>,
+    ) -> impl Iterator<Item = (&'a G, Group<'a, E, S>, Option<Group<'a, E2, S2>>)>
+    where
+        S2: GroupStorage<E2>,
+    {
+        self.outer_join(other)
+            .filter_map(|(group, elements, other_elements)| {
+                Some((group, elements?, other_elements))
+            })
+    }
+
+    /// Returns an iterator over the groups of `self` or `other`, along with their elements in
+    /// each if the group exists there.
+    #[allow(clippy::type_complexity)]
+    pub fn outer_join<'a, E2, S2>(
+        &'a self,
+        other: &'a SortedGroups<G, E2, S2>,
+    ) -> impl Iterator<Item = (&'a G, Option<Group<'a, E, S>>, Option<Group<'a, E2, S2>>)>
+    where
+        S2: GroupStorage<E2>,
+    {
+        OuterJoin {
+            left: self,
+            left_groups: self.groups.iter().peekable(),
+            right_groups: other.groups_ordered(self.descending_groups).peekable(),
+        }
+        .map(|(group, elements, other_elements)| {
+            (
+                group,
+                elements.map(Group::new),
+                other_elements.map(Group::new),
+            )
+        })
+    }
+
+    /// Returns an iterator over the elements of both `self` and `other`, along with their group,
+    /// sorted by group in the order of `self` and then by element without building a merged copy.
+    ///
+    /// Elements present in both are yielded twice, the one from `self` first.
+    pub fn merge_iter<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (&'a G, &'a E)>
+    where
+        E: Ord,
+    {
+        let mut left = self.iter().peekable();
+        let mut right = other
+            .groups_ordered(self.descending_groups)
+            .flat_map(|(group, elements)| elements.iter().map(move |element| (group, element)))
+            .peekable();
+        std::iter::from_fn(move || match (left.peek(), right.peek()) {
+            (Some((left_group, left_element)), Some((right_group, right_element)))
+                if self
+                    .cmp_groups(*right_group, *left_group)
+                    .then_with(|| right_element.cmp(left_element))
+                    .is_lt() =>
+            {
+                right.next()
+            }
+            (Some(_), _) => left.next(),
+            (None, _) => right.next(),
+        })
+    }
+}
+
+// Walks the groups of both instances in the order of the left one, pairing groups with the same
+// key
+struct OuterJoin<'a, G, E, S, S2>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    left: &'a SortedGroups<G, E, S>,
+    left_groups: Peekable<slice::Iter<'a, (G, S)>>,
+    right_groups: Peekable<Reversible<slice::Iter<'a, (G, S2)>>>,
+}
+
+impl<'a, G, E, S, S2> Iterator for OuterJoin<'a, G, E, S, S2>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    type Item = (&'a G, Option<&'a S>, Option<&'a S2>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.left_groups.peek(), self.right_groups.peek()) {
+            (Some((left_group, _)), Some((right_group, _))) => {
+                self.left.cmp_groups(left_group, right_group)
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        match ordering {
+            Ordering::Less => {
+                let (group, elements) = self.left_groups.next()?;
+                Some((group, Some(elements), None))
+            }
+            Ordering::Greater => {
+                let (group, elements) = self.right_groups.next()?;
+                Some((group, None, Some(elements)))
+            }
+            Ordering::Equal => {
+                let (group, elements) = self.left_groups.next()?;
+                let (_, other_elements) = self.right_groups.next()?;
+                Some((group, Some(elements), Some(other_elements)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_joins() {
+        let tasks = SortedGroups::<u32, &str>::from_pairs(vec![(1, "write"), (2, "review")]);
+        let events = SortedGroups::<u32, u32>::from_pairs(vec![(2, 900), (3, 1400)]);
+
+        assert_eq!(
+            tasks
+                .join(&events)
+                .map(|(day, tasks, events)| (
+                    day,
+                    tasks.iter().collect::<Vec<_>>(),
+                    events.iter().collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(&2, vec![&"review"], vec![&900])]
+        );
+        assert_eq!(
+            tasks
+                .left_join(&events)
+                .map(|(day, _, events)| (*day, events.map(|events| events.len())))
+                .collect::<Vec<_>>(),
+            vec![(1, None), (2, Some(1))]
+        );
+        assert_eq!(
+            tasks
+                .outer_join(&events)
+                .map(|(day, tasks, events)| (*day, tasks.is_some(), events.is_some()))
+                .collect::<Vec<_>>(),
+            vec![(1, true, false), (2, true, true), (3, false, true)]
+        );
+    }
+
+    #[test]
+    fn test_joins_across_group_orders() {
+        let tasks = SortedGroups::<u32, &str>::from_pairs(vec![(1, "write"), (2, "review")]);
+        let events = SortedGroups::<u32, u32>::from_pairs(vec![(2, 900), (3, 1400)]);
+        let outer_join = |tasks: &SortedGroups<u32, &str>, events: &SortedGroups<u32, u32>| {
+            tasks
+                .outer_join(events)
+                .map(|(day, tasks, events)| (*day, tasks.is_some(), events.is_some()))
+                .collect::<Vec<_>>()
+        };
+
+        let descending_tasks = tasks.clone().reverse_groups();
+        let descending_events = events.clone().reverse_groups();
+        assert_eq!(
+            outer_join(&descending_tasks, &descending_events),
+            vec![(3, false, true), (2, true, true), (1, true, false)]
+        );
+        assert_eq!(
+            outer_join(&tasks, &descending_events),
+            vec![(1, true, false), (2, true, true), (3, false, true)]
+        );
+        assert_eq!(
+            outer_join(&descending_tasks, &events),
+            vec![(3, false, true), (2, true, true), (1, true, false)]
+        );
+        assert_eq!(
+            descending_tasks
+                .join(&events)
+                .map(|(day, _, _)| *day)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_merge_iter() {
+        let a = SortedGroups::<i32, i32>::new(vec![11, 13, 31], |e| e / 10);
+        let b = SortedGroups::<i32, i32>::new(vec![12, 13, 21], |e| e / 10);
+
+        assert_eq!(
+            a.merge_iter(&b).map(|(_, e)| *e).collect::<Vec<_>>(),
+            vec![11, 12, 13, 13, 21, 31]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Exportable log of changes, see [`SortedGroups::journal`].
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ApplyError, Change, SortedGroups};
+
+/// Changes recorded by the history of a [`SortedGroups`], to be replayed onto a copy of the state
+/// history was enabled on, see [`SortedGroups::replay`].
+///
+/// With the `serde` feature, a journal is serialized as the sequence of its changes, e.g. to be
+/// persisted for crash recovery or sent to another process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Journal<G, E> {
+    changes: Vec<Change<G, E>>,
+}
+
+impl<G, E> Journal<G, E> {
+    pub fn changes(&self) -> &[Change<G, E>] {
+        &self.changes
+    }
+
+    pub fn into_changes(self) -> Vec<Change<G, E>> {
+        self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl<G, E> Default for Journal<G, E> {
+    fn default() -> Self {
+        Self {
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl<G, E> From<Vec<Change<G, E>>> for Journal<G, E> {
+    fn from(changes: Vec<Change<G, E>>) -> Self {
+        Self { changes }
+    }
+}
+
+impl<G, E> IntoIterator for Journal<G, E> {
+    type Item = Change<G, E>;
+    type IntoIter = std::vec::IntoIter<Change<G, E>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Returns the changes recorded until the current version, leaving out those that were
+    /// undone, see [`enable_history`](Self::enable_history).
+    ///
+    /// The journal is empty if history is not enabled.
+    pub fn journal(&self) -> Journal<G, E> {
+        let changes = self
+            .history
+            .as_ref()
+            .map_or(&[][..], |history| history.recorded());
+        Journal::from(changes.to_vec())
+    }
+
+    /// Takes the changes recorded until the current version, which can no longer be undone, so
+    /// that the next journal starts from the current version.
+    pub fn take_journal(&mut self) -> Journal<G, E> {
+        let changes = self
+            .history
+            .as_mut()
+            .map_or_else(Vec::new, |history| history.take_recorded());
+        Journal::from(changes)
+    }
+
+    /// Applies the changes of `journal`, bringing a copy of the state it was recorded from up to
+    /// date.
+    ///
+    /// As with [`apply`](Self::apply), replaying stops at the first change that doesn't match the
+    /// current state.
+    pub fn replay(&mut self, journal: Journal<G, E>) -> Result<(), ApplyError> {
+        self.apply(journal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_replay_journal() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        let mut replica = sorted_groups.clone();
+        sorted_groups.enable_history();
+
+        sorted_groups.insert(12);
+        sorted_groups.remove_group(&2);
+        sorted_groups.insert(31);
+        sorted_groups.undo();
+        assert_eq!(sorted_groups.journal().len(), 0);
+
+        sorted_groups.snapshot();
+        sorted_groups.insert(12);
+        sorted_groups.insert(31);
+        sorted_groups.snapshot();
+        sorted_groups.insert(32);
+        sorted_groups.undo();
+        let journal = sorted_groups.take_journal();
+        assert_eq!(journal.len(), 3);
+        assert!(!sorted_groups.undo());
+        assert!(sorted_groups.journal().is_empty());
+
+        replica.replay(journal).unwrap();
+        assert_eq!(replica, sorted_groups);
+    }
+}
@@ -41,15 +41,178 @@
 //! assert_eq!(iter.next(), None);
 //! ```
 //!
-use std::collections::{btree_map::BTreeMap, btree_set, BTreeSet};
+//! # Storage
+//!
+//! The elements of each group are held in a [`GroupStorage`], selected by the last type parameter
+//! of [`SortedGroups`]:
+//!
+//! - `BTreeSet<E>`, the default, keeps elements sorted and unique.
+//! - [`SortedVec<E>`] keeps elements sorted and allows duplicates, see [`SortedGroupsMulti`].
+//! - `Vec<E>` keeps elements in insertion order without requiring `E: Ord`, see
+//!   [`SortedGroupsInsertionOrder`].
+//!
+//! Other containers can be used by implementing [`GroupStorage`], along with [`GroupLookup`] for
+//! lookups by element.
+//!
+//! # Features
+//!
+//! - `allocator_api`: adds `SortedGroupsIn`, holding elements in memory from a custom
+//!   allocator. Requires a nightly compiler, so `--all-features` only builds on nightly.
+//! - `arbitrary`: implements `arbitrary::Arbitrary` for `SortedGroups`, for fuzzing.
+//! - `arc-swap`: adds `SwappableSortedGroups`, whose readers load snapshots without locking.
+//! - `futures`: adds a stream of changes, see `SortedGroups::subscribe`.
+//! - `msgpack`: adds compact versioned binary snapshots in MessagePack, see
+//!   `SortedGroups::to_bytes`. Enables `serde`.
+//! - `proptest`: implements `proptest::arbitrary::Arbitrary` for `SortedGroups`, along with
+//!   `SortedGroups::strategy` keeping a grouping function.
+//! - `rand`: adds random sampling of elements, see `SortedGroups::sample`.
+//! - `rayon`: adds parallel iterators over `SortedGroups`.
+//! - `rkyv`: implements zero-copy archiving of `SortedGroups`, whose `ArchivedSortedGroups`
+//!   can be iterated and queried without deserializing.
+//! - `serde`: implements `Serialize` and `Deserialize` for `SortedGroups` and `Journal`, and adds
+//!   streaming deserialization of elements, see `SortedGroups::deserialize_elements`.
+//! - `strict`: debug asserts the invariants checked by `SortedGroups::check_invariants` after
+//!   every mutation, except for the grouping of elements.
+//!
+#![cfg_attr(feature = "allocator_api", feature(allocator_api, btreemap_alloc))]
+use history::History;
+use observer::Observer;
+use offsets::GroupOffsets;
+use secondary_index::NamedIndex;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::{btree_map::BTreeMap, BTreeSet, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Index, Range, RangeBounds},
+    sync::{Arc, OnceLock},
+};
+use summary::Summaries;
+
+#[cfg(feature = "allocator_api")]
+mod alloc_impl;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "arc-swap")]
+mod arc_swap_impl;
+mod builder;
+mod by;
+mod chunks;
+mod cursor;
+mod diff;
+mod display;
+mod entry;
+mod group;
+mod group_key;
+mod history;
+mod invariants;
+mod iter_ext;
+mod join;
+mod journal;
+#[cfg(feature = "msgpack")]
+mod msgpack_impl;
+mod observer;
+mod offsets;
+mod page;
+mod persistent;
+#[cfg(feature = "proptest")]
+mod proptest_impl;
+#[cfg(feature = "rand")]
+mod rand_impl;
+#[cfg(feature = "rayon")]
+mod rayon_impl;
+#[cfg(feature = "rkyv")]
+mod rkyv_impl;
+mod secondary_index;
+mod sectioned;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod set_ops;
+mod shared;
+mod stats;
+mod storage;
+#[cfg(feature = "futures")]
+mod stream_impl;
+mod summary;
+mod transaction;
+mod transform;
+mod view;
+
+#[cfg(feature = "allocator_api")]
+pub use alloc_impl::{BTreeSetIn, SortedGroupsIn};
+#[cfg(feature = "arc-swap")]
+pub use arc_swap_impl::SwappableSortedGroups;
+pub use builder::{DuplicateError, DuplicatePolicy, SortedGroupsBuilder};
+pub use by::{Compare, SortedGroupsBy, SortedVecBy};
+pub use cursor::{Cursor, CursorMut};
+pub use diff::{ApplyError, Change};
+pub use display::DisplayGroups;
+pub use entry::{GroupEntry, OccupiedGroupEntry, VacantGroupEntry};
+pub use group::{Group, GroupIter};
+pub use group_key::GroupKey;
+pub use history::Snapshot;
+pub use invariants::InvariantViolation;
+pub use iter_ext::IntoSortedGroups;
+pub use journal::Journal;
+#[cfg(feature = "msgpack")]
+pub use msgpack_impl::BytesError;
+pub use page::{Page, PageCursor};
+pub use persistent::PersistentSortedGroups;
+#[cfg(feature = "rayon")]
+pub use rayon_impl::SortedGroupsParIter;
+#[cfg(feature = "rkyv")]
+pub use rkyv_impl::ArchivedSortedGroups;
+pub use sectioned::{SectionItem, SectionedIter};
+pub use shared::SharedSortedGroups;
+pub use storage::{GroupLookup, GroupStorage, SortedVec};
+pub use summary::GroupSummary;
+pub use transaction::Tx;
+pub use view::SortedGroupsView;
 
-#[derive(Clone, Debug)]
-pub struct SortedGroups<G, E>
+/// Function used to compute the group of an element.
+type GroupFn<E, G> = Arc<dyn Fn(&E) -> G + Send + Sync>;
+
+/// [`SortedGroups`] allowing duplicate elements, kept sorted within each group.
+pub type SortedGroupsMulti<G, E> = SortedGroups<G, E, SortedVec<E>>;
+
+/// [`SortedGroups`] keeping the elements of each group in insertion order, which doesn't require
+/// `E: Ord`.
+pub type SortedGroupsInsertionOrder<G, E> = SortedGroups<G, E, Vec<E>>;
+
+/// Elements sorted by group, each group holding its elements in a storage `S`, see
+/// [`GroupStorage`].
+pub struct SortedGroups<G, E, S = BTreeSet<E>>
 where
     G: Ord,
-    E: Ord,
+    S: GroupStorage<E>,
 {
-    groups: BTreeMap<G, BTreeSet<E>>,
+    // Groups sorted by key, in descending order if `descending_groups` is set, each key appearing
+    // once. Empty groups are only present if explicitly added or if `keep_empty_groups` is set
+    groups: Vec<(G, S)>,
+    // Missing when groups are given explicitly along with elements
+    group_from_element: Option<GroupFn<E, G>>,
+    // Total number of elements, kept up to date by every mutation
+    len: usize,
+    // Flat index of the first element of each group, computed on demand, updated by element
+    // insertions and removals and reset by other mutations
+    offsets: OnceLock<GroupOffsets>,
+    // Whether groups left empty by a removal are kept
+    keep_empty_groups: bool,
+    // Whether groups are sorted in descending order, which every positional API follows
+    descending_groups: bool,
+    // Notified of every change, not carried over to clones
+    observer: Option<Observer<G, E>>,
+    // Summary of each group, in the same order as `groups`
+    summaries: Option<Box<dyn Summaries<E>>>,
+    // Changes recorded to be undone, not carried over to clones
+    history: Option<History<G, E>>,
+    // Secondary indexes by name
+    indexes: Vec<NamedIndex<G, E>>,
+    // Whether elements may be in another group than the one of the grouping function, in which
+    // case `strict` doesn't assert their grouping
+    #[cfg(feature = "strict")]
+    regrouped: bool,
 }
 
 impl<G, E> SortedGroups<G, E>
@@ -57,151 +220,3202 @@ where
     G: Ord,
     E: Ord,
 {
+    /// Creates a new `SortedGroups` from `elements`.
+    ///
+    /// `group_from_element` is kept to compute the group of elements inserted later on.
     pub fn new(
         elements: impl IntoIterator<Item = E>,
-        group_from_element: impl Fn(&E) -> G,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+    ) -> Self {
+        Self::new_with_storage(elements, group_from_element)
+    }
+
+    /// Creates a new `SortedGroups` from `(group, element)` pairs.
+    ///
+    /// No grouping function is available afterwards, so elements must be inserted with
+    /// [`insert_with_group`](Self::insert_with_group).
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (G, E)>) -> Self {
+        Self::from_pairs_with_storage(pairs)
+    }
+
+    /// Creates a new `SortedGroups` from `elements` already sorted by group and then by element,
+    /// see [`new`](Self::new).
+    ///
+    /// Elements are appended to the last group instead of being looked up. Duplicate elements are
+    /// dropped. Unsorted elements are inserted as usual, after failing a debug assertion.
+    pub fn from_sorted_iter(
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
     ) -> Self {
+        let mut sorted_groups = Self::from_parts(Vec::new(), None);
+        for element in elements {
+            let group = group_from_element(&element);
+            let appended = match sorted_groups.groups.last_mut() {
+                Some((last, elements)) if *last == group => elements.push(element),
+                Some((last, _)) if *last > group => Err(element),
+                _ => {
+                    sorted_groups
+                        .groups
+                        .push((group, BTreeSet::from([element])));
+                    sorted_groups.len += 1;
+                    continue;
+                }
+            };
+            match appended {
+                Ok(()) => sorted_groups.len += 1,
+                Err(element) => {
+                    // Only duplicates are expected to be rejected
+                    let inserted = sorted_groups.insert_with_group(group, element);
+                    debug_assert!(!inserted, "elements are not sorted by group and element");
+                }
+            }
+        }
+        sorted_groups.group_from_element = Some(Arc::new(group_from_element));
+        sorted_groups
+    }
+
+    /// Creates a new `SortedGroups` from `elements`, failing with the first error returned by
+    /// `group_from_element`.
+    ///
+    /// As the grouping function can fail, it is not kept, so elements must be inserted with
+    /// [`insert_with_group`](Self::insert_with_group) afterwards.
+    pub fn try_new<Err>(
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> Result<G, Err>,
+    ) -> Result<Self, Err> {
         let mut groups = BTreeMap::<G, BTreeSet<E>>::new();
+        for element in elements {
+            groups
+                .entry(group_from_element(&element)?)
+                .or_default()
+                .insert(element);
+        }
+        Ok(Self::from_parts(groups.into_iter().collect(), None))
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Creates a new `SortedGroups` from `elements`, stored in `S`, see [`new`](SortedGroups::new).
+    pub fn new_with_storage(
+        elements: impl IntoIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+    ) -> Self {
+        let mut groups = BTreeMap::<G, S>::new();
         for element in elements {
             groups
                 .entry(group_from_element(&element))
                 .or_default()
                 .insert(element);
         }
-        Self { groups }
+        let mut sorted_groups = Self::from_parts(
+            groups.into_iter().collect(),
+            Some(Arc::new(group_from_element)),
+        );
+        sorted_groups.debug_follow_grouping();
+        sorted_groups
+    }
+
+    /// Creates a new `SortedGroups` from `(group, element)` pairs, stored in `S`, see
+    /// [`from_pairs`](SortedGroups::from_pairs).
+    pub fn from_pairs_with_storage(pairs: impl IntoIterator<Item = (G, E)>) -> Self {
+        let mut groups = BTreeMap::<G, S>::new();
+        for (group, element) in pairs {
+            groups.entry(group).or_default().insert(element);
+        }
+        Self::from_parts(groups.into_iter().collect(), None)
+    }
+
+    // `groups` must be sorted by key, without duplicate keys
+    fn from_parts(groups: Vec<(G, S)>, group_from_element: Option<GroupFn<E, G>>) -> Self {
+        let len = groups.iter().map(|(_, elements)| elements.len()).sum();
+        // Groups given along with a grouping function may not follow it
+        #[cfg(feature = "strict")]
+        let regrouped = group_from_element.is_some();
+        Self {
+            groups,
+            group_from_element,
+            len,
+            offsets: OnceLock::new(),
+            keep_empty_groups: false,
+            descending_groups: false,
+            observer: None,
+            summaries: None,
+            history: None,
+            indexes: Vec::new(),
+            #[cfg(feature = "strict")]
+            regrouped,
+        }
+    }
+
+    /// Sets whether groups left empty by a removal are kept instead of being dropped.
+    ///
+    /// Groups that are already empty are left untouched.
+    pub fn set_keep_empty_groups(&mut self, keep_empty_groups: bool) {
+        self.keep_empty_groups = keep_empty_groups;
+    }
+
+    /// Returns whether groups left empty by a removal are kept, see
+    /// [`set_keep_empty_groups`](Self::set_keep_empty_groups).
+    pub fn keep_empty_groups(&self) -> bool {
+        self.keep_empty_groups
+    }
+
+    /// Returns whether groups are sorted in descending order, see
+    /// [`reverse_groups`](Self::reverse_groups).
+    pub fn descending_groups(&self) -> bool {
+        self.descending_groups
+    }
+
+    /// Adds `group` without any element, if it doesn't exist yet.
+    ///
+    /// The group is kept until removed with [`remove_group`](Self::remove_group) or, unless empty
+    /// groups are kept, until it gets and loses elements.
+    ///
+    /// Returns whether the group was newly added.
+    pub fn add_group(&mut self, group: G) -> bool {
+        match self.group_index(&group) {
+            Ok(_) => false,
+            Err(index) => {
+                self.insert_group(index, group);
+                true
+            }
+        }
+    }
+
+    /// Returns the group `element` belongs to, according to the grouping function.
+    ///
+    /// Returns `None` if there is no grouping function, see [`from_pairs`](Self::from_pairs).
+    pub fn group_of(&self, element: &E) -> Option<G> {
+        self.group_from_element
+            .as_ref()
+            .map(|group_from_element| group_from_element(element))
+    }
+
+    /// Inserts `element` in its group, creating the group if needed.
+    ///
+    /// Returns whether the element was newly inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`from_pairs`](Self::from_pairs).
+    pub fn insert(&mut self, element: E) -> bool {
+        let group = self
+            .group_of(&element)
+            .expect("SortedGroups has no grouping function, use insert_with_group");
+        self.insert_with_group(group, element)
+    }
+
+    /// Inserts `element` in `group`, bypassing the grouping function.
+    ///
+    /// Returns whether the element was newly inserted.
+    pub fn insert_with_group(&mut self, group: G, element: E) -> bool {
+        self.debug_place_in_group(&group, &element);
+        let index = self.group_index_or_insert(group);
+        self.insert_in_group(index, element)
+    }
+
+    /// Inserts `element` in its group, replacing and returning the equal element if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`from_pairs`](Self::from_pairs).
+    pub fn replace(&mut self, element: E) -> Option<E>
+    where
+        S: GroupLookup<E, E>,
+    {
+        let group = self
+            .group_of(&element)
+            .expect("SortedGroups has no grouping function, use insert_with_group");
+        let Ok(index) = self.group_index(&group) else {
+            self.insert_with_group(group, element);
+            return None;
+        };
+        let previous = self.take_from_group(index, &element);
+        self.insert_in_group(index, element);
+        previous
+    }
+
+    /// Inserts `element` in its group, creating the group if needed, or gives it back in an error
+    /// if the group already has an equal element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`from_pairs`](Self::from_pairs).
+    pub fn try_insert(&mut self, element: E) -> Result<(), DuplicateError<E>>
+    where
+        S: GroupLookup<E, E>,
+    {
+        let group = self
+            .group_of(&element)
+            .expect("SortedGroups has no grouping function, use insert_with_group");
+        if self.contains(&group, &element) {
+            return Err(DuplicateError::new(element));
+        }
+        self.insert_with_group(group, element);
+        Ok(())
+    }
+
+    /// Gets the entry of `group` to inspect or modify it in place.
+    pub fn entry(&mut self, group: G) -> GroupEntry<'_, G, E, S> {
+        GroupEntry::new(self, group)
+    }
+
+    /// Removes `element` from `group`, dropping the group if it becomes empty.
+    ///
+    /// Returns whether the element was present.
+    pub fn remove<Q, R>(&mut self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.take(group, element).is_some()
+    }
+
+    /// Removes and returns the element equal to `element` from `group`, dropping the group if it
+    /// becomes empty.
+    ///
+    /// If the storage allows duplicates, only the first of the equal elements is removed.
+    pub fn take<Q, R>(&mut self, group: &Q, element: &R) -> Option<E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        let element = self.take_from_group(index, element)?;
+        self.drop_group_if_empty(index);
+        Some(element)
+    }
+
+    /// Moves `element` from the group `from` to the group `to`, creating it if needed and dropping
+    /// `from` if it becomes empty.
+    ///
+    /// Returns whether the element was present. If `to` already has an equal element, that
+    /// element is kept as with [`insert_with_group`](Self::insert_with_group).
+    pub fn move_element<Q, R>(&mut self, from: &Q, element: &R, to: G) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        let Some(element) = self.take(from, element) else {
+            return false;
+        };
+        self.insert_with_group(to, element);
+        true
+    }
+
+    /// Retains only the elements for which `f` returns `true`, dropping groups that become empty.
+    pub fn retain(&mut self, mut f: impl FnMut(&G, &E) -> bool) {
+        // When observed, `f` has already been called on every element to notify removals
+        let mut kept = self.notify_retain(&mut f).map(Vec::into_iter);
+        let keep_empty_groups = self.keep_empty_groups;
+        self.groups.retain_mut(|(group, elements)| {
+            let len = elements.len();
+            elements.retain(|element| match &mut kept {
+                Some(kept) => kept.next().unwrap_or(true),
+                None => f(group, element),
+            });
+            keep_empty_groups || elements.len() == len || !elements.is_empty()
+        });
+        self.len = self.groups.iter().map(|(_, elements)| elements.len()).sum();
+        self.invalidate_offsets();
+    }
+
+    /// Removes `group` and returns its elements.
+    pub fn remove_group<Q>(&mut self, group: &Q) -> Option<S>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        Some(self.remove_group_at(index).1)
+    }
+
+    /// Moves all elements of the group `src` into the group `dst`, creating it if needed, and
+    /// removes `src`.
+    ///
+    /// Nothing happens if `src` doesn't exist.
+    pub fn merge_groups<Q>(&mut self, src: &Q, dst: G)
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Some(elements) = self.remove_group(src) else {
+            return;
+        };
+        for element in elements.iter() {
+            self.debug_place_in_group(&dst, element);
+        }
+        let index = match self.group_index::<G>(&dst) {
+            Ok(index) => index,
+            Err(index) if !self.is_observed() => {
+                self.len += elements.len();
+                self.groups.insert(index, (dst, elements));
+                self.invalidate_offsets();
+                return;
+            }
+            Err(index) => {
+                self.insert_group(index, dst);
+                index
+            }
+        };
+        for element in elements.into_elements() {
+            self.insert_in_group(index, element);
+        }
+    }
+
+    /// Moves all groups and elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// Groups whose key is only in one of both are moved as a whole, elements of groups present in
+    /// both are inserted in the group of `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        for (group, elements) in &other.groups {
+            for element in elements.iter() {
+                self.debug_place_in_group(group, element);
+            }
+        }
+        let mut other_groups = std::mem::take(&mut other.groups);
+        if other.descending_groups != self.descending_groups {
+            other_groups.reverse();
+        }
+        let mut other_groups = other_groups.into_iter().peekable();
+        other.len = 0;
+        other.invalidate_offsets();
+
+        if self.is_observed() {
+            // Insert one element at a time to notify each of them
+            for (group, elements) in other_groups {
+                let index = self.group_index_or_insert(group);
+                for element in elements.into_elements() {
+                    self.insert_in_group(index, element);
+                }
+            }
+            return;
+        }
+
+        let groups = std::mem::take(&mut self.groups);
+        let mut merged = Vec::with_capacity(groups.len().max(other_groups.len()));
+        for (group, mut elements) in groups {
+            while let Some(other_group) = other_groups
+                .next_if(|(other_group, _)| self.cmp_groups(other_group, &group) == Ordering::Less)
+            {
+                merged.push(other_group);
+            }
+            if let Some((_, other_elements)) =
+                other_groups.next_if(|(other_group, _)| other_group == &group)
+            {
+                for element in other_elements.into_elements() {
+                    elements.insert(element);
+                }
+            }
+            merged.push((group, elements));
+        }
+        merged.extend(other_groups);
+
+        self.len = merged.iter().map(|(_, elements)| elements.len()).sum();
+        self.groups = merged;
+        self.invalidate_offsets();
+    }
+
+    /// Combines `self` and `other` into a single `SortedGroups`, see [`append`](Self::append).
+    ///
+    /// The grouping function and settings of `self` are kept.
+    pub fn merge(mut self, mut other: Self) -> Self {
+        self.append(&mut other);
+        self
+    }
+
+    /// Splits off `group` and the groups after it into a new `SortedGroups`, sharing the grouping
+    /// function and settings of `self`.
+    ///
+    /// These are the groups greater than or equal to `group`, or less than or equal to it with
+    /// [`descending_groups`](Self::descending_groups).
+    pub fn split_off<Q>(&mut self, group: &Q) -> Self
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.groups_before(group);
+        self.split_off_groups(index)
+    }
+
+    /// Removes all groups and elements.
+    pub fn clear(&mut self) {
+        if self.is_observed() {
+            self.split_off_groups(0);
+            return;
+        }
+        self.groups.clear();
+        self.len = 0;
+        self.invalidate_offsets();
+    }
+
+    /// Removes all elements of `group`, which is then dropped unless empty groups are kept.
+    pub fn clear_group<Q>(&mut self, group: &Q)
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if !self.keep_empty_groups {
+            self.remove_group(group);
+            return;
+        }
+        if let Ok(index) = self.group_index(group) {
+            self.notify_removals(index, |_| true);
+            self.len -= std::mem::take(&mut self.groups[index].1).len();
+            self.invalidate_offsets();
+        }
+    }
+
+    /// Removes `group` and returns an iterator over its elements, which is empty if the group
+    /// doesn't exist.
+    pub fn drain_group<Q>(&mut self, group: &Q) -> impl Iterator<Item = E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_group(group)
+            .into_iter()
+            .flat_map(GroupStorage::into_elements)
     }
 
     pub fn len(&self) -> usize {
-        self.groups.values().map(|v| v.len()).sum()
+        self.len
     }
 
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns the element at the flat `index`, along with its group.
+    ///
+    /// The group is located in `O(log g)` with an index over group sizes, the element is then
+    /// reached with [`GroupStorage::nth`], in `O(1)` for [`SortedVec`] and `Vec` but by walking
+    /// the group from the closest end for `BTreeSet`.
     pub fn get(&self, index: usize) -> Option<(&G, &E)> {
-        self.iter().nth(index)
+        let (group_index, element) = self.locate(index)?;
+        Some((&self.groups[group_index].0, element))
+    }
+
+    /// Returns the element at the flat `index`, along with its group, the reverse operation of
+    /// [`rank`](Self::rank).
+    ///
+    /// This is the same as [`get`](Self::get), under its order statistics name.
+    pub fn select(&self, index: usize) -> Option<(&G, &E)> {
+        self.get(index)
+    }
+
+    /// Returns the group containing the element at the flat `index`, along with the offset of that
+    /// element within the group.
+    pub fn group_at(&self, index: usize) -> Option<(&G, usize)> {
+        let (group_index, offset) = self.locate_group(index)?;
+        Some((&self.groups[group_index].0, offset))
+    }
+
+    /// Returns the span of flat indices occupied by `group`, which is empty if the group has no
+    /// elements.
+    pub fn group_range<Q>(&self, group: &Q) -> Option<Range<usize>>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let group_index = self.group_index(group).ok()?;
+        let start = self.offset(group_index);
+        Some(start..start + self.groups[group_index].1.len())
+    }
+
+    /// Returns an iterator over groups along with the span of flat indices each occupies, as
+    /// returned by [`group_range`](Self::group_range), in a single pass.
+    pub fn group_boundaries(&self) -> impl Iterator<Item = (&G, Range<usize>)> {
+        self.groups.iter().scan(0, |start, (group, elements)| {
+            let range = *start..*start + elements.len();
+            *start = range.end;
+            Some((group, range))
+        })
+    }
+
+    /// Returns the flat index of `element` in `group`.
+    ///
+    /// The group offset is found in `O(log g)` with an index over group sizes, the element rank is
+    /// then computed by walking its group.
+    pub fn position_of<Q, R>(&self, group: &Q, element: &R) -> Option<usize>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        let group_index = self.group_index(group).ok()?;
+        let rank = self.groups[group_index].1.rank(element)?;
+        Some(self.offset(group_index) + rank)
+    }
+
+    /// Returns the number of elements before `element` in `group`, i.e. the flat index it has or
+    /// would have once inserted.
+    ///
+    /// Neither `group` nor `element` need to exist, see [`select`](Self::select) for the reverse
+    /// operation. The group offset is found in `O(log g)` with an index over group sizes, the
+    /// elements before `element` are then found with [`GroupLookup::lower_bound`], in `O(log k)`
+    /// for [`SortedVec`] but linear in the group size for `BTreeSet`, which has no order
+    /// statistics.
+    pub fn rank<Q, R>(&self, group: &Q, element: &R) -> usize
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        match self.group_index(group) {
+            Ok(index) => self.offset(index) + self.groups[index].1.lower_bound(element),
+            Err(index) => self.offset(index),
+        }
+    }
+
+    /// Returns the first element, along with its group.
+    pub fn first(&self) -> Option<(&G, &E)> {
+        self.iter().next()
+    }
+
+    /// Returns the last element, along with its group.
+    pub fn last(&self) -> Option<(&G, &E)> {
+        self.iter().next_back()
+    }
+
+    /// Returns the first group and its elements.
+    pub fn first_group(&self) -> Option<(&G, Group<'_, E, S>)> {
+        self.groups
+            .first()
+            .map(|(group, elements)| (group, Group::new(elements)))
+    }
+
+    /// Returns the last group and its elements.
+    pub fn last_group(&self) -> Option<(&G, Group<'_, E, S>)> {
+        self.groups
+            .last()
+            .map(|(group, elements)| (group, Group::new(elements)))
+    }
+
+    /// Returns an iterator over the elements within the flat index `range`, along with their
+    /// group.
+    ///
+    /// The iteration starts at `range.start` the same way [`get`](Self::get) locates an element.
+    pub fn iter_range(
+        &self,
+        range: Range<usize>,
+    ) -> impl DoubleEndedIterator<Item = (&G, &E)> + ExactSizeIterator {
+        let start = range.start.min(self.len);
+        let end = range.end.clamp(start, self.len);
+        self.iter_from(start).take(end - start)
     }
 
     pub fn groups_len(&self) -> usize {
         self.groups.len()
     }
 
-    pub fn iter_groups(&self) -> impl Iterator<Item = (&G, &BTreeSet<E>)> {
-        self.groups.iter()
+    /// Converts into an iterator over groups and their elements.
+    pub fn into_groups(self) -> impl Iterator<Item = (G, S)> {
+        self.groups.into_iter()
     }
-}
 
-pub struct SortedGroupsIter<'a, G, E> {
-    // Iterator over groups
-    groups_iter: std::collections::btree_map::Iter<'a, G, BTreeSet<E>>,
-    // Current group and its iterator
-    current_group: Option<(&'a G, btree_set::Iter<'a, E>)>,
-}
+    /// Converts into a list of groups and their elements in iteration order, including empty
+    /// groups.
+    pub fn into_grouped_vec(self) -> Vec<(G, Vec<E>)> {
+        self.groups
+            .into_iter()
+            .map(|(group, elements)| (group, elements.into_elements().collect()))
+            .collect()
+    }
 
-impl<G, E> SortedGroups<G, E>
-where
-    G: Ord,
-    E: Ord,
-{
-    pub fn iter(&self) -> SortedGroupsIter<'_, G, E> {
-        let mut groups_iter = self.groups.iter();
-        let current_group = groups_iter.next().map(|(g, v)| (g, v.iter()));
+    /// Converts into a map of groups to their elements, including empty groups.
+    pub fn into_inner(self) -> BTreeMap<G, S> {
+        self.groups.into_iter().collect()
+    }
 
-        SortedGroupsIter {
-            groups_iter,
-            current_group,
+    /// Returns an iterator over groups and their elements, which can be reversed to get groups in
+    /// descending order.
+    pub fn iter_groups(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&G, Group<'_, E, S>)> + ExactSizeIterator {
+        self.groups
+            .iter()
+            .map(|(group, elements)| (group, Group::new(elements)))
+    }
+
+    /// Returns an iterator over groups, each with a lazy iterator over its elements, including
+    /// empty groups.
+    pub fn iter_grouped(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&G, impl DoubleEndedIterator<Item = &E>)> + ExactSizeIterator
+    {
+        self.groups
+            .iter()
+            .map(|(group, elements)| (group, elements.iter()))
+    }
+
+    /// Returns an iterator over coarser groups `f(group)`, each with the elements of the adjacent
+    /// groups mapping to it, e.g. to show day groups by month.
+    ///
+    /// Groups are merged lazily without copying elements. `f` is expected to preserve the order
+    /// of groups, otherwise a coarser group appears once per run of adjacent groups mapping to it.
+    pub fn project_groups<G2: Ord>(
+        &self,
+        f: impl Fn(&G) -> G2,
+    ) -> impl Iterator<Item = (G2, impl Iterator<Item = &E>)> {
+        let mut rest = &self.groups[..];
+        std::iter::from_fn(move || {
+            let ((first, _), others) = rest.split_first()?;
+            let projected = f(first);
+            let len = 1 + others
+                .iter()
+                .take_while(|(group, _)| f(group) == projected)
+                .count();
+            let (merged, others) = rest.split_at(len);
+            rest = others;
+            Some((
+                projected,
+                merged.iter().flat_map(|(_, elements)| elements.iter()),
+            ))
+        })
+    }
+
+    /// Returns an iterator over groups, including empty ones.
+    pub fn groups(&self) -> impl DoubleEndedIterator<Item = &G> + ExactSizeIterator {
+        self.groups.iter().map(|(group, _)| group)
+    }
+
+    /// Returns an iterator over elements, without their group.
+    pub fn elements(&self) -> impl DoubleEndedIterator<Item = &E> + ExactSizeIterator {
+        self.iter().map(|(_, element)| element)
+    }
+
+    /// Returns an iterator over groups in descending order and their elements, each group still
+    /// yielding its elements in order.
+    ///
+    /// This is the same as [`iter`](Self::iter) with
+    /// [`descending_groups`](Self::descending_groups), see
+    /// [`reverse_groups`](Self::reverse_groups) to have positional APIs follow descending groups.
+    pub fn iter_desc_groups(&self) -> impl DoubleEndedIterator<Item = (&G, &E)> {
+        self.groups_ordered(true)
+            .flat_map(|(group, elements)| elements.iter().map(move |element| (group, element)))
+    }
+
+    /// Returns an iterator over the groups within `range` and their elements.
+    pub fn range_groups<Q, R>(&self, range: R) -> impl Iterator<Item = (&G, Group<'_, E, S>)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.groups[self.group_indices(range)]
+            .iter()
+            .map(|(group, elements)| (group, Group::new(elements)))
+    }
+
+    /// Returns whether `group` contains `element`.
+    pub fn contains<Q, R>(&self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.group_elements(group)
+            .is_some_and(|elements| elements.contains(element))
+    }
+
+    /// Returns the number of elements of `group` equal to `element`, which is at most one unless
+    /// the storage allows duplicates, see [`SortedGroupsMulti`].
+    pub fn count<Q, R>(&self, group: &Q, element: &R) -> usize
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        self.group_elements(group)
+            .map_or(0, |elements| elements.count(element))
+    }
+
+    /// Returns whether `group` exists, even if it has no elements.
+    pub fn contains_group<Q>(&self, group: &Q) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_index(group).is_ok()
+    }
+
+    /// Returns an iterator over the elements of `group`, which is empty if the group doesn't
+    /// exist.
+    pub fn iter_group<Q>(&self, group: &Q) -> impl Iterator<Item = &E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_elements(group)
+            .into_iter()
+            .flat_map(GroupStorage::iter)
+    }
+
+    /// Returns the number of elements in `group`.
+    pub fn group_len<Q>(&self, group: &Q) -> usize
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_elements(group).map_or(0, GroupStorage::len)
+    }
+
+    /// Returns an iterator over groups along with their number of elements, including empty
+    /// groups.
+    ///
+    /// Storages keep track of their length, so elements are not counted.
+    pub fn group_sizes(&self) -> impl DoubleEndedIterator<Item = (&G, usize)> + ExactSizeIterator {
+        self.groups
+            .iter()
+            .map(|(group, elements)| (group, elements.len()))
+    }
+
+    /// Returns a map of groups to their number of elements, see
+    /// [`group_sizes`](Self::group_sizes).
+    pub fn group_sizes_map(&self) -> BTreeMap<&G, usize> {
+        self.group_sizes().collect()
+    }
+
+    /// Returns the first element of `group`, without iterating the group.
+    pub fn first_of_group<Q>(&self, group: &Q) -> Option<&E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_elements(group)?.iter().next()
+    }
+
+    /// Returns the last element of `group`, without iterating the group.
+    pub fn last_of_group<Q>(&self, group: &Q) -> Option<&E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_elements(group)?.iter().next_back()
+    }
+
+    fn group_elements<Q>(&self, group: &Q) -> Option<&S>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        Some(&self.groups[index].1)
+    }
+
+    fn group_index<Q>(&self, group: &Q) -> Result<usize, usize>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.groups
+            .binary_search_by(|(g, _)| self.cmp_groups(g.borrow(), group))
+    }
+
+    // Compares groups in the order they are stored, reversed with descending groups
+    fn cmp_groups<Q: Ord + ?Sized>(&self, group: &Q, other: &Q) -> Ordering {
+        let ordering = group.cmp(other);
+        if self.descending_groups {
+            ordering.reverse()
+        } else {
+            ordering
         }
     }
-}
 
-impl<'a, G, E> Iterator for SortedGroupsIter<'a, G, E>
-where
-    G: Ord,
-    E: Ord,
-{
-    type Item = (&'a G, &'a E);
+    // Number of groups stored before `group`, which is its position if it exists
+    fn groups_before<Q>(&self, group: &Q) -> usize
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.groups
+            .partition_point(|(g, _)| self.cmp_groups(g.borrow(), group) == Ordering::Less)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match &mut self.current_group {
-                Some((group, iter)) => {
-                    if let Some(element) = iter.next() {
-                        return Some((*group, element));
-                    } else {
-                        // Current group is exhausted, move to next group
-                        self.current_group = self.groups_iter.next().map(|(g, v)| (g, v.iter()));
-                    }
-                }
-                None => return None,
-            }
+    // Iterator over groups from the least one if `descending_groups` is false, or from the
+    // greatest one otherwise
+    fn groups_ordered(&self, descending_groups: bool) -> Reversible<std::slice::Iter<'_, (G, S)>> {
+        Reversible {
+            iter: self.groups.iter(),
+            reversed: descending_groups != self.descending_groups,
         }
     }
-}
 
-// Implement IntoIterator for reference
-impl<'a, G, E> IntoIterator for &'a SortedGroups<G, E>
-where
-    G: Ord,
-    E: Ord,
-{
-    type Item = (&'a G, &'a E);
-    type IntoIter = SortedGroupsIter<'a, G, E>;
+    // Positions of the groups within `range`
+    fn group_indices<Q, R>(&self, range: R) -> Range<usize>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let before_start = |group: &Q| match range.start_bound() {
+            Bound::Included(start) => group < start,
+            Bound::Excluded(start) => group <= start,
+            Bound::Unbounded => false,
+        };
+        let after_end = |group: &Q| match range.end_bound() {
+            Bound::Included(end) => group > end,
+            Bound::Excluded(end) => group >= end,
+            Bound::Unbounded => false,
+        };
+        // Groups past the end of the range come first once descending
+        let (start, end) = if self.descending_groups {
+            (
+                self.groups.partition_point(|(g, _)| after_end(g.borrow())),
+                self.groups
+                    .partition_point(|(g, _)| !before_start(g.borrow())),
+            )
+        } else {
+            (
+                self.groups
+                    .partition_point(|(g, _)| before_start(g.borrow())),
+                self.groups.partition_point(|(g, _)| !after_end(g.borrow())),
+            )
+        };
+        start..end.max(start)
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    // Position of the group containing the element at the flat `index`, along with the element
+    fn locate(&self, index: usize) -> Option<(usize, &E)> {
+        let (group_index, offset) = self.locate_group(index)?;
+        let element = self.groups[group_index].1.nth(offset)?;
+        Some((group_index, element))
     }
-}
 
-impl<G, E> PartialEq for SortedGroups<G, E>
-where
-    G: Ord,
-    E: Ord,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.groups.eq(&other.groups)
+    // Position of the group containing the element at the flat `index`, along with the offset of
+    // the element in that group
+    fn locate_group(&self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.len {
+            return None;
+        }
+        Some(self.offsets().locate(index))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Moves the groups from `index` into a new `SortedGroups` sharing the grouping function and
+    // settings of `self`
+    fn split_off_groups(&mut self, index: usize) -> Self {
+        let groups = if self.is_observed() {
+            // Remove groups from the last one to notify their removal
+            let mut groups = Vec::new();
+            while self.groups.len() > index {
+                groups.push(self.remove_group_at(self.groups.len() - 1));
+            }
+            groups.reverse();
+            groups
+        } else {
+            let groups = self.groups.split_off(index);
+            self.len -= groups
+                .iter()
+                .map(|(_, elements)| elements.len())
+                .sum::<usize>();
+            self.invalidate_offsets();
+            groups
+        };
+        let mut other = Self::from_parts(groups, self.group_from_element.clone());
+        other.keep_empty_groups = self.keep_empty_groups;
+        other.descending_groups = self.descending_groups;
+        other
+    }
 
-    #[derive(PartialEq, Eq, Ord, Debug)]
-    struct Element {
-        group: i32,
-        value: i32,
+    fn offsets(&self) -> &GroupOffsets {
+        self.offsets.get_or_init(|| {
+            GroupOffsets::new(self.groups.iter().map(|(_, elements)| elements.len()))
+        })
+    }
+
+    // Flat index of the first element of the group at `index`, which is `len` if `index` is the
+    // number of groups
+    fn offset(&self, index: usize) -> usize {
+        self.offsets().offset(index)
+    }
+
+    fn invalidate_offsets(&mut self) {
+        self.offsets.take();
+        self.debug_check_invariants();
     }
 
-    impl PartialOrd for Element {
-        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-            Some(self.cmp(other))
+    // Sorts groups in descending order or not, for instances nothing observes yet
+    fn sort_groups(&mut self, descending_groups: bool) {
+        if descending_groups != self.descending_groups {
+            self.groups.reverse();
+            self.descending_groups = descending_groups;
+            self.invalidate_offsets();
         }
     }
 
-    #[test]
-    fn test_empty_sorted_groups() {
-        let sorted_groups = SortedGroups::<i32, Element>::new(vec![].into_iter(), |e| e.group);
-        assert_eq!(sorted_groups.len(), 0);
+    // Accounts for `delta` elements inserted in or removed from the group at `index`, updating
+    // offsets in place rather than invalidating them
+    fn resize_group(&mut self, index: usize, delta: isize) {
+        self.len = self.len.wrapping_add_signed(delta);
+        if let Some(offsets) = self.offsets.get_mut() {
+            offsets.resize(index, delta);
+        }
+        self.debug_check_invariants();
     }
 
-    #[test]
-    fn test_insert_sorted_groups() {
-        let sorted_groups = SortedGroups::<i32, Element>::new(
-            vec![
-                Element { group: 1, value: 1 },
-                Element { group: 1, value: 2 },
-                Element { group: 2, value: 1 },
-            ],
-            |e| e.group,
-        );
+    // Drops the group at `index` after a removal left it empty, unless empty groups are kept
+    fn drop_group_if_empty(&mut self, index: usize) {
+        if !self.keep_empty_groups && self.groups[index].1.is_empty() {
+            self.remove_group_at(index);
+        }
+    }
 
-        assert_eq!(sorted_groups.len(), 3);
-        assert_eq!(sorted_groups.groups_len(), 2);
-        let mut iter = sorted_groups.iter();
-        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 1 })));
-        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 2 })));
-        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 1 })));
-        assert_eq!(iter.next(), None);
+    // The following primitives keep `len` and offsets up to date and notify the observer and
+    // summaries. Every mutation goes through them, except for bulk shortcuts taken when nothing
+    // observes changes
+
+    // Position of `group`, which is inserted without elements if it doesn't exist
+    fn group_index_or_insert(&mut self, group: G) -> usize {
+        match self.group_index(&group) {
+            Ok(index) => index,
+            Err(index) => {
+                self.insert_group(index, group);
+                index
+            }
+        }
+    }
+
+    // Inserts `group` without elements at `index` among groups
+    fn insert_group(&mut self, index: usize, group: G) {
+        self.insert_group_with(index, group, S::default());
+    }
+
+    // Same as `insert_group` with the given empty storage
+    fn insert_group_with(&mut self, index: usize, group: G, elements: S) {
+        self.groups.insert(index, (group, elements));
+        self.invalidate_offsets();
+        self.notify_group(index, true);
+    }
+
+    // Appends `element` to `group` if both come last, saving their lookups, and inserts it as
+    // usual otherwise. Returns whether the element was newly inserted
+    fn push_or_insert(&mut self, group: G, element: E) -> bool {
+        let index = match self.groups.last() {
+            Some((last, _)) if *last == group => self.groups.len() - 1,
+            Some((last, _)) if self.cmp_groups(last, &group) == Ordering::Greater => {
+                return self.insert_with_group(group, element)
+            }
+            _ => {
+                let index = self.groups.len();
+                self.insert_group(index, group);
+                index
+            }
+        };
+        if self.is_observed() {
+            return self.insert_in_group(index, element);
+        }
+        match self.groups[index].1.push(element) {
+            Ok(()) => {
+                self.resize_group(index, 1);
+                true
+            }
+            Err(element) => self.insert_in_group(index, element),
+        }
+    }
+
+    // Inserts `element` in the group at `index`, returning whether it was newly inserted
+    fn insert_in_group(&mut self, index: usize, element: E) -> bool {
+        if self.is_observed() {
+            return self.insert_in_group_at(index, element).is_some();
+        }
+        if !self.groups[index].1.insert(element) {
+            return false;
+        }
+        self.resize_group(index, 1);
+        true
+    }
+
+    // Same as `insert_in_group`, returning the rank of the element in its group if inserted
+    fn insert_in_group_at(&mut self, index: usize, element: E) -> Option<usize> {
+        let rank = self.groups[index].1.insert_at(element)?;
+        self.resize_group(index, 1);
+        self.notify_element(index, rank, true);
+        Some(rank)
+    }
+
+    // Removes and returns the element equal to `element` from the group at `index`, keeping the
+    // group even if it becomes empty
+    fn take_from_group<R>(&mut self, index: usize, element: &R) -> Option<E>
+    where
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        if self.is_observed() {
+            let rank = self.groups[index].1.rank(element)?;
+            self.notify_element(index, rank, false);
+        }
+        let element = self.groups[index].1.take(element)?;
+        self.resize_group(index, -1);
+        Some(element)
+    }
+
+    // Removes the first or last element of the group at `index`, keeping the group even if it
+    // becomes empty
+    fn pop_from_group(&mut self, index: usize, first: bool) -> Option<E> {
+        let elements = &self.groups[index].1;
+        let rank = if first {
+            0
+        } else {
+            elements.len().checked_sub(1)?
+        };
+        self.notify_element(index, rank, false);
+        let elements = &mut self.groups[index].1;
+        let element = if first {
+            elements.pop_first()
+        } else {
+            elements.pop_last()
+        }?;
+        self.resize_group(index, -1);
+        Some(element)
+    }
+
+    // Removes the element at `rank` in the group at `index`, keeping the group even if it becomes
+    // empty
+    fn remove_from_group(&mut self, index: usize, rank: usize) -> Option<E> {
+        self.notify_element(index, rank, false);
+        let element = self.groups[index].1.remove_nth(rank)?;
+        self.resize_group(index, -1);
+        Some(element)
+    }
+
+    // Removes the group at `index` along with its elements
+    fn remove_group_at(&mut self, index: usize) -> (G, S) {
+        self.notify_removals(index, |_| true);
+        self.notify_group(index, false);
+        let (group, elements) = self.groups.remove(index);
+        self.len -= elements.len();
+        self.invalidate_offsets();
+        (group, elements)
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E>,
+{
+    /// Removes all elements, returning them along with their group in iteration order.
+    ///
+    /// The structure is emptied even if the returned iterator is not fully consumed.
+    pub fn drain(&mut self) -> impl ExactSizeIterator<Item = (G, E)> {
+        self.split_off_groups(0).into_iter()
+    }
+
+    /// Removes the elements for which `pred` returns `true` and returns them along with their
+    /// group in iteration order, dropping groups that become empty.
+    ///
+    /// Unlike `BTreeSet::extract_if`, `pred` is called on every element upfront and matching
+    /// elements are removed even if the returned iterator is not fully consumed.
+    pub fn extract_if(
+        &mut self,
+        mut pred: impl FnMut(&G, &E) -> bool,
+    ) -> impl ExactSizeIterator<Item = (G, E)> {
+        // When observed, `pred` has already been called on every element to notify removals
+        let mut kept = self
+            .notify_retain(|group, element| !pred(group, element))
+            .map(Vec::into_iter);
+        let mut extracted = Vec::new();
+        let mut extracted_len = 0;
+        let keep_empty_groups = self.keep_empty_groups;
+        self.groups.retain_mut(|(group, elements)| {
+            let matching = elements.extract_if(|element| match &mut kept {
+                Some(kept) => !kept.next().unwrap_or(true),
+                None => pred(group, element),
+            });
+            if matching.is_empty() {
+                return true;
+            }
+            extracted_len += matching.len();
+            extracted.push((group.clone(), matching));
+            keep_empty_groups || !elements.is_empty()
+        });
+        self.len -= extracted_len;
+        self.invalidate_offsets();
+        SortedGroupsIntoIter::new(extracted, extracted_len)
+    }
+
+    /// Splits into the elements before the flat `index` and the elements from `index`, the group
+    /// straddling `index` being split in two.
+    ///
+    /// Both parts share the grouping function and settings of `self`.
+    pub fn split_at(mut self, index: usize) -> (Self, Self) {
+        let Some((group_index, offset)) = self.locate_group(index) else {
+            let tail = self.split_off_groups(self.groups.len());
+            return (self, tail);
+        };
+        if offset == 0 {
+            let tail = self.split_off_groups(group_index);
+            return (self, tail);
+        }
+        let mut tail = self.split_off_groups(group_index + 1);
+        self.notify_removals(group_index, |rank| rank >= offset);
+        let (group, elements) = &mut self.groups[group_index];
+        let (group, tail_elements) = (group.clone(), elements.split_off(offset));
+        self.len -= tail_elements.len();
+        self.invalidate_offsets();
+        tail.len += tail_elements.len();
+        tail.groups.insert(0, (group, tail_elements));
+        tail.invalidate_offsets();
+        (self, tail)
+    }
+
+    /// Removes and returns the first element along with its group, dropping the group if it
+    /// becomes empty.
+    pub fn pop_first(&mut self) -> Option<(G, E)> {
+        let index = self
+            .groups
+            .iter()
+            .position(|(_, elements)| !elements.is_empty())?;
+        let element = self.pop_from_group(index, true)?;
+        Some((self.take_group_key(index), element))
+    }
+
+    /// Removes and returns the last element along with its group, dropping the group if it
+    /// becomes empty.
+    pub fn pop_last(&mut self) -> Option<(G, E)> {
+        let index = self
+            .groups
+            .iter()
+            .rposition(|(_, elements)| !elements.is_empty())?;
+        let element = self.pop_from_group(index, false)?;
+        Some((self.take_group_key(index), element))
+    }
+
+    /// Removes `element` from `group`, transforms it with `f` and inserts the result back in the
+    /// group given by the grouping function, or in `group` if there is none.
+    ///
+    /// Returns whether the element was present. If the transformed element is equal to another
+    /// element of its new group, that element is kept as with [`insert`](Self::insert).
+    pub fn update<Q, R>(&mut self, group: &Q, element: &R, f: impl FnOnce(E) -> E) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        S: GroupLookup<E, R>,
+        R: ?Sized,
+    {
+        let Ok(index) = self.group_index(group) else {
+            return false;
+        };
+        let Some(element) = self.take_from_group(index, element) else {
+            return false;
+        };
+        let group = self.take_group_key(index);
+        let element = f(element);
+        let group = self.group_of(&element).unwrap_or(group);
+        self.insert_with_group(group, element);
+        true
+    }
+
+    /// Returns a hash map of groups to clones of their elements in iteration order, including
+    /// empty groups.
+    pub fn to_hash_map(&self) -> HashMap<G, Vec<E>>
+    where
+        G: Hash,
+        E: Clone,
+    {
+        self.groups
+            .iter()
+            .map(|(group, elements)| (group.clone(), elements.iter().cloned().collect()))
+            .collect()
+    }
+
+    // Returns the key of the group at `index` after an element was removed from it, moved out of
+    // the group if it has become empty
+    fn take_group_key(&mut self, index: usize) -> G {
+        if !self.keep_empty_groups && self.groups[index].1.is_empty() {
+            self.remove_group_at(index).0
+        } else {
+            self.groups[index].0.clone()
+        }
+    }
+}
+
+// Iterator yielding the items of `iter` from either end
+pub(crate) struct Reversible<I> {
+    iter: I,
+    reversed: bool,
+}
+
+impl<I: DoubleEndedIterator> Iterator for Reversible<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reversed {
+            self.iter.next_back()
+        } else {
+            self.iter.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Reversible<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.reversed {
+            self.iter.next()
+        } else {
+            self.iter.next_back()
+        }
+    }
+}
+
+pub struct SortedGroupsIter<'a, G, E, S = BTreeSet<E>>
+where
+    E: 'a,
+    S: GroupStorage<E> + 'a,
+{
+    // Iterator over groups not yet reached from either end
+    groups_iter: std::slice::Iter<'a, (G, S)>,
+    // Group being iterated from the front and its iterator
+    front_group: Option<(&'a G, S::Iter<'a>)>,
+    // Group being iterated from the back and its iterator
+    back_group: Option<(&'a G, S::Iter<'a>)>,
+    // Number of elements left to yield from both ends
+    remaining: usize,
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    pub fn iter(&self) -> SortedGroupsIter<'_, G, E, S> {
+        SortedGroupsIter {
+            groups_iter: self.groups.iter(),
+            front_group: None,
+            back_group: None,
+            remaining: self.len,
+        }
+    }
+
+    /// Returns an iterator over groups and elements along with their flat index, as passed to
+    /// [`get`](Self::get).
+    pub fn iter_with_index(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (usize, &G, &E)> + ExactSizeIterator {
+        self.iter()
+            .enumerate()
+            .map(|(index, (group, element))| (index, group, element))
+    }
+
+    /// Returns an iterator over groups and elements starting at the flat `index`, which is empty
+    /// if `index` is out of bounds.
+    ///
+    /// The starting element is located the same way as with [`get`](Self::get), without iterating
+    /// over the preceding elements.
+    pub fn iter_from(&self, index: usize) -> SortedGroupsIter<'_, G, E, S> {
+        let Some((group_index, offset)) = self.locate_group(index) else {
+            return SortedGroupsIter {
+                groups_iter: [].iter(),
+                front_group: None,
+                back_group: None,
+                remaining: 0,
+            };
+        };
+        let (group, elements) = &self.groups[group_index];
+        SortedGroupsIter {
+            groups_iter: self.groups[group_index + 1..].iter(),
+            front_group: Some((group, elements.iter_from(offset))),
+            back_group: None,
+            remaining: self.len - index,
+        }
+    }
+
+    /// Returns an iterator over groups and elements starting at the first element of `group`, or
+    /// of the next group if it has no elements.
+    pub fn iter_from_group<Q>(&self, group: &Q) -> SortedGroupsIter<'_, G, E, S>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let group_index = self.groups_before(group);
+        let skipped = self.offset(group_index);
+        SortedGroupsIter {
+            groups_iter: self.groups[group_index..].iter(),
+            front_group: None,
+            back_group: None,
+            remaining: self.len - skipped,
+        }
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Returns an iterator over groups and elements starting strictly after `element` in `group`.
+    ///
+    /// Neither `group` nor `element` need to exist, so that iteration can resume from a position
+    /// whose element was removed since.
+    pub fn iter_after<Q, R>(
+        &self,
+        group: &Q,
+        element: &R,
+    ) -> impl DoubleEndedIterator<Item = (&G, &E)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        let (current, next_index) = match self.group_index(group) {
+            Ok(index) => (Some(&self.groups[index]), index + 1),
+            Err(index) => (None, index),
+        };
+        // The range is built right away so that the iterator doesn't borrow `element`
+        let current = current
+            .map(|(group, elements)| {
+                elements
+                    .range::<R, _>((Bound::Excluded(element), Bound::Unbounded))
+                    .map(move |element| (group, element))
+            })
+            .into_iter()
+            .flatten();
+        let next = self.groups[next_index..]
+            .iter()
+            .flat_map(|(group, elements)| elements.iter().map(move |element| (group, element)));
+        current.chain(next)
+    }
+
+    /// Returns the element following `element` in `group`, which is the first element of the
+    /// next non-empty group at the end of `group`.
+    ///
+    /// Neither `group` nor `element` need to exist, see [`iter_after`](Self::iter_after).
+    pub fn next_after<Q, R>(&self, group: &Q, element: &R) -> Option<(&G, &E)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.iter_after(group, element).next()
+    }
+
+    /// Returns the element preceding `element` in `group`, which is the last element of the
+    /// previous non-empty group at the start of `group`.
+    ///
+    /// Neither `group` nor `element` need to exist.
+    pub fn prev_before<Q, R>(&self, group: &Q, element: &R) -> Option<(&G, &E)>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        let previous_groups = match self.group_index(group) {
+            Ok(index) => {
+                let (group, elements) = &self.groups[index];
+                let previous = elements
+                    .range::<R, _>((Bound::Unbounded, Bound::Excluded(element)))
+                    .next_back();
+                if let Some(previous) = previous {
+                    return Some((group, previous));
+                }
+                &self.groups[..index]
+            }
+            Err(index) => &self.groups[..index],
+        };
+        previous_groups
+            .iter()
+            .rev()
+            .find_map(|(group, elements)| Some((group, elements.last()?)))
+    }
+
+    /// Returns an iterator over the elements of `group` within `range`, which is empty if the group
+    /// doesn't exist or if the range is inverted or empty.
+    pub fn range_in_group<Q, R, B>(
+        &self,
+        group: &Q,
+        range: B,
+    ) -> impl DoubleEndedIterator<Item = &E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+        B: RangeBounds<R>,
+    {
+        self.group_elements(group)
+            .map(|elements| Group::new(elements).range(range))
+            .into_iter()
+            .flatten()
+    }
+}
+
+impl<'a, G, E, S> Iterator for SortedGroupsIter<'a, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    type Item = (&'a G, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = loop {
+            if let Some((group, iter)) = &mut self.front_group {
+                if let Some(element) = iter.next() {
+                    break (*group, element);
+                }
+            }
+            // Current group is exhausted, move to next group
+            match self.groups_iter.next() {
+                Some((g, v)) => self.front_group = Some((g, v.iter())),
+                None => {
+                    // Remaining elements are in the group being iterated from the back
+                    let (group, iter) = self.back_group.as_mut()?;
+                    break (*group, iter.next()?);
+                }
+            }
+        };
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<G, E, S> DoubleEndedIterator for SortedGroupsIter<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = loop {
+            if let Some((group, iter)) = &mut self.back_group {
+                if let Some(element) = iter.next_back() {
+                    break (*group, element);
+                }
+            }
+            // Current group is exhausted, move to previous group
+            match self.groups_iter.next_back() {
+                Some((g, v)) => self.back_group = Some((g, v.iter())),
+                None => {
+                    // Remaining elements are in the group being iterated from the front
+                    let (group, iter) = self.front_group.as_mut()?;
+                    break (*group, iter.next_back()?);
+                }
+            }
+        };
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<G, E, S> ExactSizeIterator for SortedGroupsIter<'_, G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+}
+
+// Implement IntoIterator for reference
+impl<'a, G, E, S> IntoIterator for &'a SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    type Item = (&'a G, &'a E);
+    type IntoIter = SortedGroupsIter<'a, G, E, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct SortedGroupsIntoIter<G, E, S = BTreeSet<E>>
+where
+    S: GroupStorage<E>,
+{
+    // Iterator over remaining groups
+    groups_iter: std::vec::IntoIter<(G, S)>,
+    // Current group and its iterator
+    current_group: Option<(G, S::IntoIter)>,
+    // Number of elements left to yield
+    remaining: usize,
+}
+
+impl<G, E, S> SortedGroupsIntoIter<G, E, S>
+where
+    S: GroupStorage<E>,
+{
+    fn new(groups: Vec<(G, S)>, len: usize) -> Self {
+        let mut groups_iter = groups.into_iter();
+        let current_group = groups_iter.next().map(|(g, v)| (g, v.into_elements()));
+
+        Self {
+            groups_iter,
+            current_group,
+            remaining: len,
+        }
+    }
+}
+
+impl<G, E, S> Iterator for SortedGroupsIntoIter<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E>,
+{
+    type Item = (G, E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (group, iter) = self.current_group.as_mut()?;
+            match iter.next() {
+                Some(element) if iter.len() > 0 => {
+                    self.remaining -= 1;
+                    return Some((group.clone(), element));
+                }
+                element => {
+                    // Current group is exhausted, move to next group
+                    let next_group = self.groups_iter.next().map(|(g, v)| (g, v.into_elements()));
+                    let (group, _) = std::mem::replace(&mut self.current_group, next_group)?;
+                    if let Some(element) = element {
+                        // The last element of a group takes the group key instead of a clone
+                        self.remaining -= 1;
+                        return Some((group, element));
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<G, E, S> ExactSizeIterator for SortedGroupsIntoIter<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E>,
+{
+}
+
+impl<G, E, S> IntoIterator for SortedGroups<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E>,
+{
+    type Item = (G, E);
+    type IntoIter = SortedGroupsIntoIter<G, E, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SortedGroupsIntoIter::new(self.groups, self.len)
+    }
+}
+
+impl<G, E, S> FromIterator<(G, E)> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn from_iter<I: IntoIterator<Item = (G, E)>>(iter: I) -> Self {
+        Self::from_pairs_with_storage(iter)
+    }
+}
+
+/// Takes groups and their elements as is, empty groups included. The result has no grouping
+/// function, as if built with [`SortedGroups::from_pairs`].
+impl<G, E, S> From<BTreeMap<G, S>> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn from(groups: BTreeMap<G, S>) -> Self {
+        Self::from_parts(groups.into_iter().collect(), None)
+    }
+}
+
+/// Inserts elements in their group computed by the grouping function.
+///
+/// # Panics
+///
+/// Panics if there is no grouping function, see [`SortedGroups::from_pairs`].
+impl<G, E, S> Extend<E> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.insert(element);
+        }
+    }
+}
+
+impl<G, E, S> Extend<(G, E)> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn extend<I: IntoIterator<Item = (G, E)>>(&mut self, iter: I) {
+        for (group, element) in iter {
+            self.insert_with_group(group, element);
+        }
+    }
+}
+
+// Implemented by hand to leave out the observer
+impl<G, E, S> Clone for SortedGroups<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            groups: self.groups.clone(),
+            group_from_element: self.group_from_element.clone(),
+            len: self.len,
+            offsets: self.offsets.clone(),
+            keep_empty_groups: self.keep_empty_groups,
+            descending_groups: self.descending_groups,
+            observer: None,
+            summaries: self
+                .summaries
+                .as_ref()
+                .map(|summaries| summaries.clone_box()),
+            history: None,
+            indexes: self
+                .indexes
+                .iter()
+                .map(|(name, index)| (*name, index.clone_box()))
+                .collect(),
+            #[cfg(feature = "strict")]
+            regrouped: self.regrouped,
+        }
+    }
+}
+
+/// The alternate form `{:#?}` uses the layout of [`SortedGroups::display`] with `Debug`
+/// formatting of groups and elements.
+impl<G, E, S> fmt::Debug for SortedGroups<G, E, S>
+where
+    G: Ord + fmt::Debug,
+    E: fmt::Debug,
+    S: GroupStorage<E> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return self.display().write_with(f, G::fmt, E::fmt);
+        }
+        f.debug_struct("SortedGroups")
+            .field("groups", &self.groups)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<G, E, S> PartialEq for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E> + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.groups == other.groups
+    }
+}
+
+impl<G, E, S> Eq for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E> + Eq,
+{
+}
+
+/// Compares groups lexicographically, each group by key and then by its elements, so that empty
+/// groups are taken into account as with `PartialEq`.
+impl<G, E, S> PartialOrd for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E> + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.groups.partial_cmp(&other.groups)
+    }
+}
+
+impl<G, E, S> Ord for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E> + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.groups.cmp(&other.groups)
+    }
+}
+
+impl<G, E, S> Hash for SortedGroups<G, E, S>
+where
+    G: Ord + Hash,
+    S: GroupStorage<E> + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.groups.hash(state);
+    }
+}
+
+/// Returns the element at a flat index, so that `sorted_groups[i]` reads like indexing a `Vec`.
+///
+/// `Index` must return a reference into `self`, which a `(&G, &E)` pair is not, so only the
+/// element is returned. Use [`SortedGroups::get`] to get the group along with the element.
+///
+/// # Panics
+///
+/// Panics if the index is out of bounds.
+impl<G, E, S> Index<usize> for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    type Output = E;
+
+    fn index(&self, index: usize) -> &E {
+        match self.get(index) {
+            Some((_, element)) => element,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {index}",
+                self.len
+            ),
+        }
+    }
+}
+
+/// Creates an empty `SortedGroups` without grouping function, as if built with
+/// [`SortedGroups::from_pairs`].
+impl<G, E, S> Default for SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn default() -> Self {
+        Self::from_parts(Vec::new(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Element {
+        group: i32,
+        value: i32,
+    }
+
+    #[test]
+    fn test_empty_sorted_groups() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(vec![], |e| e.group);
+        assert_eq!(sorted_groups.len(), 0);
+    }
+
+    #[test]
+    fn test_insert_sorted_groups() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(sorted_groups.len(), 3);
+        assert_eq!(sorted_groups.groups_len(), 2);
+        let mut iter = sorted_groups.iter();
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 1 })));
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 2 })));
+        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 1 })));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_insert_element() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 2, value: 2 },
+                Element { group: 2, value: 4 },
+            ],
+            |e| e.group,
+        );
+
+        assert!(sorted_groups.insert(Element { group: 2, value: 3 }));
+        assert!(sorted_groups.insert(Element { group: 1, value: 1 }));
+        assert!(!sorted_groups.insert(Element { group: 2, value: 4 }));
+
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(sorted_groups.groups_len(), 2);
+        let mut iter = sorted_groups.iter();
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 1 })));
+        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 2 })));
+        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 3 })));
+        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 4 })));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_insert_element_with_group() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(vec![], |e| e.group);
+
+        assert!(sorted_groups.insert_with_group(3, Element { group: 1, value: 1 }));
+
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(
+            sorted_groups.get(0),
+            Some((&3, &Element { group: 1, value: 1 }))
+        );
+    }
+
+    #[test]
+    fn test_cloned_sorted_groups_keep_grouping_function() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(vec![], |e| e.group * 10);
+        let mut cloned = sorted_groups.clone();
+
+        cloned.insert(Element { group: 1, value: 1 });
+
+        assert_eq!(cloned.get(0), Some((&10, &Element { group: 1, value: 1 })));
+        assert_eq!(
+            sorted_groups.group_of(&Element { group: 2, value: 1 }),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn test_remove_element() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert!(sorted_groups.remove(&1, &Element { group: 1, value: 1 }));
+        assert!(!sorted_groups.remove(&1, &Element { group: 1, value: 1 }));
+        assert!(!sorted_groups.remove(&3, &Element { group: 1, value: 2 }));
+
+        assert_eq!(sorted_groups.len(), 2);
+        assert_eq!(sorted_groups.groups_len(), 2);
+    }
+
+    #[test]
+    fn test_take_last_element_removes_group() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.take(&2, &Element { group: 2, value: 1 }),
+            Some(Element { group: 2, value: 1 })
+        );
+        assert_eq!(
+            sorted_groups.take(&2, &Element { group: 2, value: 1 }),
+            None
+        );
+
+        assert_eq!(sorted_groups.len(), 1);
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(
+            sorted_groups
+                .iter_groups()
+                .map(|(g, _)| *g)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_remove_group() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 2, value: 1 },
+                Element { group: 2, value: 2 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.remove_group(&2),
+            Some(BTreeSet::from([
+                Element { group: 2, value: 1 },
+                Element { group: 2, value: 2 },
+            ]))
+        );
+        assert_eq!(sorted_groups.remove_group(&2), None);
+
+        assert_eq!(sorted_groups.len(), 1);
+        assert_eq!(sorted_groups.groups_len(), 1);
+    }
+
+    #[test]
+    fn test_len_follows_mutations() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 1 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+        assert_eq!(sorted_groups.len(), 2);
+
+        sorted_groups.insert(Element { group: 2, value: 2 });
+        sorted_groups.insert(Element { group: 2, value: 2 });
+        assert_eq!(sorted_groups.len(), 3);
+
+        sorted_groups.remove(&1, &Element { group: 1, value: 1 });
+        assert_eq!(sorted_groups.len(), 2);
+
+        sorted_groups.remove_group(&2);
+        assert_eq!(sorted_groups.len(), 0);
+        assert!(sorted_groups.is_empty());
+    }
+
+    #[test]
+    fn test_get_element_by_index() {
+        let mut sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 1, value: 3 },
+                Element { group: 3, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.get(2),
+            Some((&1, &Element { group: 1, value: 3 }))
+        );
+        assert_eq!(
+            sorted_groups.get(3),
+            Some((&3, &Element { group: 3, value: 1 }))
+        );
+        assert_eq!(sorted_groups.get(4), None);
+
+        sorted_groups.insert(Element { group: 2, value: 1 });
+        assert_eq!(
+            sorted_groups.get(3),
+            Some((&2, &Element { group: 2, value: 1 }))
+        );
+        assert_eq!(
+            sorted_groups.get(4),
+            Some((&3, &Element { group: 3, value: 1 }))
+        );
+
+        sorted_groups.remove(&1, &Element { group: 1, value: 1 });
+        assert_eq!(
+            sorted_groups.get(0),
+            Some((&1, &Element { group: 1, value: 2 }))
+        );
+        assert_eq!(
+            sorted_groups.get(2),
+            Some((&2, &Element { group: 2, value: 1 }))
+        );
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            (0..sorted_groups.len())
+                .map(|index| sorted_groups.get(index).unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_iter_group() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 2, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.iter_group(&2).collect::<Vec<_>>(),
+            vec![
+                &Element { group: 2, value: 1 },
+                &Element { group: 2, value: 2 }
+            ]
+        );
+        assert_eq!(sorted_groups.iter_group(&3).next(), None);
+        assert_eq!(sorted_groups.group_len(&1), 1);
+        assert_eq!(sorted_groups.group_len(&2), 2);
+        assert_eq!(sorted_groups.group_len(&3), 0);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.into_iter().collect::<Vec<_>>(),
+            vec![
+                (1, Element { group: 1, value: 1 }),
+                (1, Element { group: 1, value: 2 }),
+                (2, Element { group: 2, value: 1 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_groups() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 2, value: 1 },
+                Element { group: 1, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        assert_eq!(
+            sorted_groups.into_groups().collect::<Vec<_>>(),
+            vec![
+                (1, BTreeSet::from([Element { group: 1, value: 1 }])),
+                (2, BTreeSet::from([Element { group: 2, value: 1 }])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_btree_map_conversion() {
+        let groups = BTreeMap::from([
+            (1, BTreeSet::from([11, 12])),
+            (2, BTreeSet::new()),
+            (3, BTreeSet::from([31])),
+        ]);
+        let sorted_groups = SortedGroups::<i32, i32>::from(groups.clone());
+
+        assert_eq!(sorted_groups.len(), 3);
+        assert_eq!(sorted_groups.groups_len(), 3);
+        assert_eq!(sorted_groups.get(2), Some((&3, &31)));
+        assert_eq!(sorted_groups.into_inner(), groups);
+    }
+
+    #[test]
+    fn test_grouped_conversions() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![12, 11, 31], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(
+            sorted_groups.to_hash_map(),
+            HashMap::from([(1, vec![11, 12]), (2, vec![]), (3, vec![31])])
+        );
+        assert_eq!(
+            sorted_groups.into_grouped_vec(),
+            vec![(1, vec![11, 12]), (2, vec![]), (3, vec![31])]
+        );
+    }
+
+    #[test]
+    fn test_std_traits() {
+        let a = SortedGroups::<i32, i32>::from_pairs([(1, 11), (2, 21)]);
+        let b = SortedGroups::<i32, i32>::from_pairs([(1, 11), (1, 12)]);
+        let mut empty_group = SortedGroups::<i32, i32>::default();
+        empty_group.add_group(1);
+
+        assert!(a < b);
+        assert!(SortedGroups::default() < empty_group);
+        assert!(empty_group < b);
+        assert_eq!(BTreeSet::from([a.clone(), b.clone(), a.clone()]).len(), 2);
+        assert_eq!(std::collections::HashSet::from([a.clone(), a, b]).len(), 2);
+    }
+
+    #[test]
+    fn test_index() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![21, 11, 12], |e| e / 10);
+
+        assert_eq!(sorted_groups[0], 11);
+        assert_eq!(sorted_groups[2], 21);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn test_index_out_of_bounds() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        let _ = sorted_groups[1];
+    }
+
+    #[test]
+    fn test_try_new() {
+        fn parse(line: &&'static str) -> Result<String, &'static str> {
+            let (date, _) = line.split_once(' ').ok_or(*line)?;
+            Ok(date.to_string())
+        }
+
+        let sorted_groups = SortedGroups::try_new(["2024-01-02 b", "2024-01-01 a"], parse).unwrap();
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(sorted_groups.group_of(&"2024-01-03 c"), None);
+        assert_eq!(
+            SortedGroups::try_new(["2024-01-01 a", "invalid"], parse).unwrap_err(),
+            "invalid"
+        );
+    }
+
+    #[test]
+    fn test_try_insert() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+
+        assert_eq!(sorted_groups.try_insert(12), Ok(()));
+        let error = sorted_groups.try_insert(11).unwrap_err();
+        assert_eq!(error.element(), &11);
+        assert_eq!(error.to_string(), "element already exists in its group");
+        assert_eq!(sorted_groups.len(), 2);
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::from_sorted_iter([11, 12, 12, 21, 31], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups,
+            SortedGroups::new([11, 12, 21, 31], |e| e / 10)
+        );
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(sorted_groups.get(2), Some((&2, &21)));
+        assert_eq!(sorted_groups.group_of(&41), Some(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "not sorted")]
+    fn test_from_sorted_iter_rejects_unsorted_input() {
+        SortedGroups::<i32, i32>::from_sorted_iter([21, 11], |e| e / 10);
+    }
+
+    #[test]
+    fn test_from_pairs() {
+        let mut sorted_groups: SortedGroups<&str, i32> =
+            vec![("b", 2), ("a", 3), ("b", 1)].into_iter().collect();
+
+        assert_eq!(sorted_groups.group_of(&1), None);
+        sorted_groups.insert_with_group("a", 1);
+
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&"a", &1), (&"a", &3), (&"b", &1), (&"b", &2)]
+        );
+        assert_eq!(
+            sorted_groups,
+            SortedGroups::from_pairs(sorted_groups.clone())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no grouping function")]
+    fn test_insert_without_grouping_function() {
+        let mut sorted_groups = SortedGroups::<&str, i32>::from_pairs(vec![("a", 1)]);
+        sorted_groups.insert(2);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut sorted_groups =
+            SortedGroups::<i32, Element>::new(vec![Element { group: 2, value: 1 }], |e| e.group);
+
+        sorted_groups.extend(vec![
+            Element { group: 1, value: 1 },
+            Element { group: 2, value: 2 },
+        ]);
+        sorted_groups.extend(vec![(3, Element { group: 1, value: 2 })]);
+
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![
+                (&1, &Element { group: 1, value: 1 }),
+                (&2, &Element { group: 2, value: 1 }),
+                (&2, &Element { group: 2, value: 2 }),
+                (&3, &Element { group: 1, value: 2 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_rev() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        let mut iter = sorted_groups.iter().rev();
+        assert_eq!(iter.next(), Some((&2, &Element { group: 2, value: 1 })));
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 2 })));
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 1 })));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_iter_from_both_ends() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 1, value: 3 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        let mut iter = sorted_groups.iter();
+        assert_eq!(
+            iter.next_back(),
+            Some((&2, &Element { group: 2, value: 1 }))
+        );
+        assert_eq!(
+            iter.next_back(),
+            Some((&1, &Element { group: 1, value: 3 }))
+        );
+        assert_eq!(iter.next(), Some((&1, &Element { group: 1, value: 1 })));
+        assert_eq!(
+            iter.next_back(),
+            Some((&1, &Element { group: 1, value: 2 }))
+        );
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn test_iter_len() {
+        let sorted_groups = SortedGroups::<i32, Element>::new(
+            vec![
+                Element { group: 1, value: 1 },
+                Element { group: 1, value: 2 },
+                Element { group: 2, value: 1 },
+            ],
+            |e| e.group,
+        );
+
+        let mut iter = sorted_groups.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        iter.next_back();
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len(), 0);
+
+        let mut into_iter = sorted_groups.into_iter();
+        assert_eq!(into_iter.len(), 3);
+        into_iter.next();
+        into_iter.next();
+        assert_eq!(into_iter.len(), 1);
+    }
+
+    #[test]
+    fn test_range_groups() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31, 51], |e| e / 10);
+
+        fn groups_in(
+            sorted_groups: &SortedGroups<i32, i32>,
+            range: impl RangeBounds<i32>,
+        ) -> Vec<i32> {
+            sorted_groups
+                .range_groups(range)
+                .map(|(group, _)| *group)
+                .collect()
+        }
+        assert_eq!(groups_in(&sorted_groups, 2..5), vec![2, 3]);
+        assert_eq!(groups_in(&sorted_groups, 2..=5), vec![2, 3, 5]);
+        assert_eq!(groups_in(&sorted_groups, ..3), vec![1, 2]);
+        assert_eq!(groups_in(&sorted_groups, 4..), vec![5]);
+        assert_eq!(
+            groups_in(&sorted_groups, (Bound::Excluded(1), Bound::Unbounded)),
+            vec![2, 3, 5]
+        );
+        assert_eq!(groups_in(&sorted_groups, 6..), Vec::<i32>::new());
+        assert_eq!(
+            sorted_groups
+                .range_groups(2..3)
+                .map(|(_, elements)| elements.len())
+                .sum::<usize>(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21, 31, 32], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.iter_range(1..4).collect::<Vec<_>>(),
+            vec![(&1, &12), (&1, &13), (&2, &21)]
+        );
+        assert_eq!(
+            sorted_groups.iter_range(3..10).rev().collect::<Vec<_>>(),
+            vec![(&3, &32), (&3, &31), (&2, &21)]
+        );
+        assert_eq!(sorted_groups.iter_range(2..5).len(), 3);
+        assert_eq!(sorted_groups.iter_range(4..4).next(), None);
+        assert_eq!(sorted_groups.iter_range(6..8).next(), None);
+        assert_eq!(
+            sorted_groups.iter_range(0..6).collect::<Vec<_>>(),
+            sorted_groups.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_groups_and_elements() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![21, 11, 12], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        assert_eq!(sorted_groups.groups().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(
+            sorted_groups.elements().rev().collect::<Vec<_>>(),
+            vec![&21, &12, &11]
+        );
+        assert_eq!(sorted_groups.elements().len(), 3);
+    }
+
+    #[test]
+    fn test_iter_from() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 41], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        assert_eq!(
+            sorted_groups.iter_from(1).collect::<Vec<_>>(),
+            vec![(&1, &12), (&2, &21), (&4, &41)]
+        );
+        assert_eq!(sorted_groups.iter_from(3).next_back(), Some((&4, &41)));
+        assert_eq!(sorted_groups.iter_from(4).next(), None);
+        assert_eq!(
+            sorted_groups.iter_from_group(&2).collect::<Vec<_>>(),
+            vec![(&2, &21), (&4, &41)]
+        );
+        assert_eq!(sorted_groups.iter_from_group(&3).len(), 1);
+        assert_eq!(sorted_groups.iter_from_group(&5).next(), None);
+    }
+
+    #[test]
+    fn test_iter_after() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 13, 21, 41], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.iter_after(&1, &11).collect::<Vec<_>>(),
+            vec![(&1, &13), (&2, &21), (&4, &41)]
+        );
+        assert_eq!(
+            sorted_groups.iter_after(&1, &12).collect::<Vec<_>>(),
+            vec![(&1, &13), (&2, &21), (&4, &41)]
+        );
+        assert_eq!(sorted_groups.iter_after(&2, &21).next(), Some((&4, &41)));
+        assert_eq!(sorted_groups.iter_after(&3, &31).next(), Some((&4, &41)));
+        assert_eq!(sorted_groups.iter_after(&4, &41).next(), None);
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![12, 11, 21, 32, 31], |e| e / 10);
+
+        assert_eq!(sorted_groups.first(), Some((&1, &11)));
+        assert_eq!(sorted_groups.last(), Some((&3, &32)));
+        let (group, elements) = sorted_groups.first_group().unwrap();
+        assert_eq!(
+            (group, elements.iter().collect::<Vec<_>>()),
+            (&1, vec![&11, &12])
+        );
+        let (group, elements) = sorted_groups.last_group().unwrap();
+        assert_eq!(
+            (group, elements.iter().collect::<Vec<_>>()),
+            (&3, vec![&31, &32])
+        );
+
+        let empty = SortedGroups::<i32, i32>::new(vec![], |e| e / 10);
+        assert_eq!(empty.first(), None);
+        assert_eq!(empty.last(), None);
+        assert_eq!(empty.first_group(), None);
+        assert_eq!(empty.last_group(), None);
+    }
+
+    #[test]
+    fn test_pop_first_and_last() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 31, 32], |e| e / 10);
+
+        assert_eq!(sorted_groups.pop_first(), Some((1, 11)));
+        assert_eq!(sorted_groups.pop_last(), Some((3, 32)));
+        assert_eq!(sorted_groups.pop_last(), Some((3, 31)));
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(sorted_groups.pop_first(), Some((1, 12)));
+        assert_eq!(sorted_groups.pop_first(), Some((2, 21)));
+        assert_eq!(sorted_groups.pop_first(), None);
+        assert_eq!(sorted_groups.pop_last(), None);
+        assert!(sorted_groups.is_empty());
+        assert_eq!(sorted_groups.groups_len(), 0);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21, 23, 32], |e| e / 10);
+
+        sorted_groups.retain(|group, element| group * 10 + 2 != *element);
+
+        assert_eq!(sorted_groups.len(), 4);
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &13), (&2, &21), (&2, &23)]
+        );
+        assert_eq!(sorted_groups.get(2), Some((&2, &21)));
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+
+        let mut drain = sorted_groups.drain();
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some((1, 11)));
+        assert_eq!(drain.collect::<Vec<_>>(), vec![(1, 12), (2, 21)]);
+        assert!(sorted_groups.is_empty());
+        assert_eq!(sorted_groups.groups_len(), 0);
+
+        sorted_groups.insert(31);
+        drop(sorted_groups.drain());
+        assert!(sorted_groups.is_empty());
+        assert_eq!(sorted_groups.get(0), None);
+    }
+
+    #[test]
+    fn test_drain_group() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.drain_group(&2).collect::<Vec<_>>(),
+            vec![21, 22]
+        );
+        assert_eq!(sorted_groups.drain_group(&2).next(), None);
+        assert_eq!(sorted_groups.len(), 2);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&3, &31)]
+        );
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 13, 22, 31, 33], |e| e / 10);
+
+        let extracted = sorted_groups.extract_if(|group, element| element % 2 == group % 2);
+
+        assert_eq!(
+            extracted.collect::<Vec<_>>(),
+            vec![(1, 11), (1, 13), (2, 22), (3, 31), (3, 33)]
+        );
+        assert_eq!(sorted_groups.len(), 1);
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(sorted_groups.get(0), Some((&1, &12)));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31], |e| e / 10);
+
+        sorted_groups.clear_group(&2);
+        sorted_groups.clear_group(&4);
+        assert_eq!(sorted_groups.len(), 2);
+        assert_eq!(sorted_groups.groups_len(), 2);
+
+        sorted_groups.clear();
+        assert!(sorted_groups.is_empty());
+        assert_eq!(sorted_groups.groups_len(), 0);
+        assert_eq!(sorted_groups.iter().next(), None);
+
+        sorted_groups.insert(41);
+        assert_eq!(sorted_groups.get(0), Some((&4, &41)));
+    }
+
+    #[test]
+    fn test_add_empty_group() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 31], |e| e / 10);
+
+        assert!(sorted_groups.add_group(2));
+        assert!(!sorted_groups.add_group(2));
+        assert!(!sorted_groups.add_group(3));
+        assert!(sorted_groups.add_group(4));
+
+        assert_eq!(sorted_groups.len(), 2);
+        assert_eq!(sorted_groups.groups_len(), 4);
+        assert_eq!(
+            sorted_groups
+                .iter_groups()
+                .map(|(group, elements)| (*group, elements.len()))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (2, 0), (3, 1), (4, 0)]
+        );
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&3, &31)]
+        );
+        assert_eq!(
+            sorted_groups.iter().rev().collect::<Vec<_>>(),
+            vec![(&3, &31), (&1, &11)]
+        );
+        assert_eq!(sorted_groups.get(1), Some((&3, &31)));
+        assert_eq!(sorted_groups.last(), Some((&3, &31)));
+        assert_eq!(sorted_groups.pop_last(), Some((3, 31)));
+        assert_eq!(sorted_groups.groups_len(), 3);
+
+        sorted_groups.insert(21);
+        sorted_groups.remove(&2, &21);
+        assert_eq!(sorted_groups.groups_len(), 2);
+        assert_eq!(
+            sorted_groups.clone().into_iter().collect::<Vec<_>>(),
+            vec![(1, 11)]
+        );
+    }
+
+    #[test]
+    fn test_keep_empty_groups() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 21, 31, 41, 51, 52, 61], |e| e / 10);
+        sorted_groups.set_keep_empty_groups(true);
+        assert!(sorted_groups.keep_empty_groups());
+
+        sorted_groups.remove(&1, &11);
+        assert_eq!(sorted_groups.pop_first(), Some((2, 21)));
+        sorted_groups.retain(|_, element| *element != 31);
+        sorted_groups
+            .extract_if(|group, _| *group == 4)
+            .for_each(drop);
+        sorted_groups.clear_group(&5);
+        assert_eq!(sorted_groups.entry(6).or_default().take(&61), Some(61));
+
+        assert!(sorted_groups.is_empty());
+        assert_eq!(
+            sorted_groups
+                .iter_groups()
+                .map(|(group, _)| *group)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(sorted_groups.first(), None);
+        assert_eq!(sorted_groups.pop_last(), None);
+
+        sorted_groups.insert(52);
+        assert_eq!(sorted_groups.get(0), Some((&5, &52)));
+        assert_eq!(sorted_groups.remove_group(&5), Some(BTreeSet::from([52])));
+        assert_eq!(sorted_groups.groups_len(), 5);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21, 22], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        assert!(sorted_groups.contains(&2, &22));
+        assert!(!sorted_groups.contains(&1, &22));
+        assert!(!sorted_groups.contains(&4, &41));
+        assert!(sorted_groups.contains_group(&1));
+        assert!(sorted_groups.contains_group(&3));
+        assert!(!sorted_groups.contains_group(&4));
+    }
+
+    #[test]
+    fn test_lookups_with_borrowed_keys() {
+        let mut sorted_groups = SortedGroups::<String, String>::from_pairs(vec![
+            ("a".to_string(), "x".to_string()),
+            ("a".to_string(), "y".to_string()),
+            ("b".to_string(), "z".to_string()),
+            ("c".to_string(), "w".to_string()),
+        ]);
+
+        assert!(sorted_groups.contains_group("a"));
+        assert!(sorted_groups.contains("a", "y"));
+        assert_eq!(sorted_groups.group_len("a"), 2);
+        assert_eq!(sorted_groups.iter_group("b").collect::<Vec<_>>(), vec!["z"]);
+        assert_eq!(
+            sorted_groups
+                .range_groups::<str, _>((Bound::Included("b"), Bound::Unbounded))
+                .map(|(group, _)| group.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        assert_eq!(sorted_groups.take("a", "x"), Some("x".to_string()));
+        assert!(sorted_groups.remove("a", "y"));
+        assert_eq!(
+            sorted_groups.drain_group("b").collect::<Vec<_>>(),
+            vec!["z"]
+        );
+        sorted_groups.clear_group("c");
+        assert_eq!(sorted_groups.remove_group("c"), None);
+        assert!(sorted_groups.is_empty());
+    }
+
+    #[test]
+    fn test_position_of() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 31, 32], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(sorted_groups.position_of(&1, &11), Some(0));
+        assert_eq!(sorted_groups.position_of(&1, &13), Some(2));
+        assert_eq!(sorted_groups.position_of(&3, &32), Some(4));
+        assert_eq!(sorted_groups.position_of(&3, &33), None);
+        assert_eq!(sorted_groups.position_of(&2, &21), None);
+        assert_eq!(sorted_groups.position_of(&4, &41), None);
+        for index in 0..sorted_groups.len() {
+            let (group, element) = sorted_groups.get(index).unwrap();
+            assert_eq!(sorted_groups.position_of(group, element), Some(index));
+        }
+    }
+
+    #[test]
+    fn test_group_boundaries() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 31, 32], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(
+            sorted_groups.group_boundaries().collect::<Vec<_>>(),
+            vec![(&1, 0..3), (&2, 3..3), (&3, 3..5)]
+        );
+    }
+
+    #[test]
+    fn test_iter_with_index() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.iter_with_index().collect::<Vec<_>>(),
+            vec![(0, &1, &11), (1, &1, &12), (2, &2, &21)]
+        );
+        assert_eq!(
+            sorted_groups.iter_with_index().next_back(),
+            Some((2, &2, &21))
+        );
+    }
+
+    #[test]
+    fn test_iter_grouped() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        let grouped = sorted_groups
+            .iter_grouped()
+            .rev()
+            .map(|(group, elements)| (*group, elements.rev().copied().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(grouped, vec![(3, vec![]), (2, vec![21]), (1, vec![12, 11])]);
+    }
+
+    #[test]
+    fn test_project_groups() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 21, 151, 152], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        let projected = sorted_groups
+            .project_groups(|group| group / 10)
+            .map(|(group, elements)| (group, elements.copied().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(projected, vec![(0, vec![11, 12, 21]), (1, vec![151, 152])]);
+    }
+
+    #[test]
+    fn test_next_after_and_prev_before() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 31], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(sorted_groups.next_after(&1, &11), Some((&1, &12)));
+        assert_eq!(sorted_groups.next_after(&1, &12), Some((&3, &31)));
+        assert_eq!(sorted_groups.next_after(&2, &25), Some((&3, &31)));
+        assert_eq!(sorted_groups.next_after(&3, &31), None);
+        assert_eq!(sorted_groups.prev_before(&3, &31), Some((&1, &12)));
+        assert_eq!(sorted_groups.prev_before(&1, &12), Some((&1, &11)));
+        assert_eq!(sorted_groups.prev_before(&4, &41), Some((&3, &31)));
+        assert_eq!(sorted_groups.prev_before(&1, &11), None);
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        assert_eq!(
+            sorted_groups.group_sizes().collect::<Vec<_>>(),
+            vec![(&1, 2), (&2, 1), (&3, 0)]
+        );
+        assert_eq!(
+            sorted_groups.group_sizes_map(),
+            BTreeMap::from([(&1, 2), (&2, 1), (&3, 0)])
+        );
+    }
+
+    #[test]
+    fn test_first_and_last_of_group() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21], |e| e / 10);
+        sorted_groups.add_group(3);
+
+        assert_eq!(sorted_groups.first_of_group(&1), Some(&11));
+        assert_eq!(sorted_groups.last_of_group(&1), Some(&13));
+        assert_eq!(sorted_groups.last_of_group(&2), Some(&21));
+        assert_eq!(sorted_groups.first_of_group(&3), None);
+        assert_eq!(sorted_groups.last_of_group(&4), None);
+    }
+
+    #[test]
+    fn test_range_in_group() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 14, 21], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.range_in_group(&1, 12..14).collect::<Vec<_>>(),
+            vec![&12, &13]
+        );
+        assert_eq!(
+            sorted_groups
+                .range_in_group(&1, (Bound::Excluded(12), Bound::Unbounded))
+                .next_back(),
+            Some(&14)
+        );
+        assert_eq!(sorted_groups.range_in_group(&3, ..).count(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_range_in_group_inverted() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13], |e| e / 10);
+
+        assert_eq!(sorted_groups.range_in_group(&1, 13..12).count(), 0);
+        assert_eq!(
+            sorted_groups
+                .range_in_group(&1, (Bound::Excluded(12), Bound::Excluded(12)))
+                .count(),
+            0
+        );
+        assert_eq!(sorted_groups.range_in_group(&1, 12..12).count(), 0);
+        assert_eq!(sorted_groups.range_in_group(&1, 12..=12).count(), 1);
+    }
+
+    #[test]
+    fn test_rank_and_select_across_mutations() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 13, 31, 32], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(sorted_groups.rank(&1, &12), 1);
+        assert_eq!(sorted_groups.rank(&2, &21), 2);
+        assert_eq!(sorted_groups.rank(&3, &35), 4);
+        assert_eq!(sorted_groups.rank(&4, &41), 4);
+
+        // Element insertions and removals update offsets in place
+        assert_eq!(sorted_groups.select(2), Some((&3, &31)));
+        sorted_groups.insert(21);
+        sorted_groups.insert(12);
+        assert!(sorted_groups.remove(&3, &31));
+        assert_eq!(sorted_groups.rank(&3, &32), 4);
+        for index in 0..sorted_groups.len() {
+            let (group, element) = sorted_groups.select(index).unwrap();
+            assert_eq!(sorted_groups.rank(group, element), index);
+        }
+        assert_eq!(sorted_groups.select(5), None);
+    }
+
+    #[test]
+    fn test_rank_with_sorted_vec() {
+        let sorted_groups = SortedGroups::<i32, i32, SortedVec<i32>>::new_with_storage(
+            vec![11, 12, 12, 14, 21],
+            |e| e / 10,
+        );
+
+        assert_eq!(sorted_groups.rank(&1, &12), 1);
+        assert_eq!(sorted_groups.rank(&1, &13), 3);
+        assert_eq!(sorted_groups.rank(&2, &25), 5);
+        assert_eq!(sorted_groups.select(3), Some((&1, &14)));
+    }
+
+    #[test]
+    fn test_group_range() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 31, 32], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        assert_eq!(sorted_groups.group_range(&1), Some(0..3));
+        assert_eq!(sorted_groups.group_range(&2), Some(3..3));
+        assert_eq!(sorted_groups.group_range(&3), Some(3..5));
+        assert_eq!(sorted_groups.group_range(&4), None);
+
+        sorted_groups.insert(21);
+        assert_eq!(sorted_groups.group_range(&2), Some(3..4));
+        assert_eq!(sorted_groups.group_range(&3), Some(4..6));
+    }
+
+    #[test]
+    fn test_group_at() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 31, 32], |e| e / 10);
+        sorted_groups.add_group(0);
+        sorted_groups.add_group(2);
+
+        assert_eq!(sorted_groups.group_at(0), Some((&1, 0)));
+        assert_eq!(sorted_groups.group_at(2), Some((&1, 2)));
+        assert_eq!(sorted_groups.group_at(3), Some((&3, 0)));
+        assert_eq!(sorted_groups.group_at(4), Some((&3, 1)));
+        assert_eq!(sorted_groups.group_at(5), None);
+        for index in 0..sorted_groups.len() {
+            let (group, offset) = sorted_groups.group_at(index).unwrap();
+            assert_eq!(
+                sorted_groups.group_range(group).unwrap().start + offset,
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_keeps_duplicates() {
+        let mut sorted_groups =
+            SortedGroupsMulti::<i32, i32>::new_with_storage(vec![12, 11, 12, 21], |e| e / 10);
+        assert!(sorted_groups.insert(11));
+
+        assert_eq!(sorted_groups.len(), 5);
+        assert_eq!(sorted_groups.count(&1, &11), 2);
+        assert_eq!(sorted_groups.count(&1, &13), 0);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &11), (&1, &12), (&1, &12), (&2, &21)]
+        );
+        assert_eq!(sorted_groups.position_of(&1, &12), Some(2));
+        assert_eq!(sorted_groups.get(3), Some((&1, &12)));
+
+        assert!(sorted_groups.remove(&1, &11));
+        assert_eq!(sorted_groups.count(&1, &11), 1);
+        assert_eq!(sorted_groups.len(), 4);
+    }
+
+    #[test]
+    fn test_count_without_duplicates() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        assert_eq!(sorted_groups.count(&1, &11), 1);
+        assert_eq!(sorted_groups.count(&3, &31), 0);
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        #[derive(Debug, PartialEq)]
+        struct Message {
+            channel: &'static str,
+            text: &'static str,
+        }
+
+        let mut sorted_groups = SortedGroupsInsertionOrder::<&str, Message>::new_with_storage(
+            vec![
+                Message {
+                    channel: "b",
+                    text: "hello",
+                },
+                Message {
+                    channel: "a",
+                    text: "hi",
+                },
+                Message {
+                    channel: "b",
+                    text: "bye",
+                },
+            ],
+            |message| message.channel,
+        );
+        sorted_groups.insert(Message {
+            channel: "a",
+            text: "hey",
+        });
+
+        assert_eq!(
+            sorted_groups
+                .iter()
+                .map(|(_, message)| message.text)
+                .collect::<Vec<_>>(),
+            vec!["hi", "hey", "hello", "bye"]
+        );
+        assert_eq!(
+            sorted_groups.get(2).map(|(_, message)| message.text),
+            Some("hello")
+        );
+        assert_eq!(
+            sorted_groups.position_of(
+                "b",
+                &Message {
+                    channel: "b",
+                    text: "bye"
+                }
+            ),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_iter_desc_groups() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 31], |e| e / 10);
+
+        assert_eq!(
+            sorted_groups.iter_desc_groups().collect::<Vec<_>>(),
+            vec![(&3, &31), (&2, &21), (&1, &11), (&1, &12)]
+        );
+        assert_eq!(
+            sorted_groups.iter_desc_groups().next_back(),
+            Some((&1, &12))
+        );
+        assert_eq!(
+            sorted_groups
+                .iter_groups()
+                .rev()
+                .map(|(group, _)| *group)
+                .collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_descending_groups() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 21, 31], |e| e / 10).reverse_groups();
+        assert!(sorted_groups.descending_groups());
+        assert!(sorted_groups.insert(41));
+        assert!(sorted_groups.insert(22));
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![
+                (&4, &41),
+                (&3, &31),
+                (&2, &21),
+                (&2, &22),
+                (&1, &11),
+                (&1, &12)
+            ]
+        );
+        assert_eq!(sorted_groups.first(), Some((&4, &41)));
+        assert_eq!(sorted_groups.last(), Some((&1, &12)));
+        assert_eq!(sorted_groups.get(3), Some((&2, &22)));
+        assert_eq!(sorted_groups.rank(&2, &22), 3);
+        assert_eq!(sorted_groups.iter_from_group(&2).next(), Some((&2, &21)));
+        assert_eq!(
+            sorted_groups
+                .range_groups(1..3)
+                .map(|(group, _)| *group)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(sorted_groups.iter_desc_groups().next(), Some((&4, &41)));
+        assert!(sorted_groups.remove(&3, &31));
+        assert_eq!(sorted_groups.get(1), Some((&2, &21)));
+
+        let lower = sorted_groups.split_off(&2);
+        assert_eq!(sorted_groups.iter().collect::<Vec<_>>(), vec![(&4, &41)]);
+        assert_eq!(lower.get(0), Some((&2, &21)));
+        assert!(lower.descending_groups());
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+        assert_eq!(lower.check_invariants(), Ok(()));
+        assert!(!lower.reverse_groups().descending_groups());
+    }
+
+    #[test]
+    fn test_append_across_group_orders() {
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 31], |e| e / 10).reverse_groups();
+        let mut other = SortedGroups::<i32, i32>::new(vec![12, 21, 41], |e| e / 10);
+
+        sorted_groups.append(&mut other);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&4, &41), (&3, &31), (&2, &21), (&1, &11), (&1, &12)]
+        );
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+
+        assert!(sorted_groups.update(&1, &12, |e| e + 1));
+        assert!(sorted_groups.update(&1, &11, |e| e + 20));
+        assert!(!sorted_groups.update(&1, &11, |e| e));
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &13), (&2, &21), (&3, &31)]
+        );
+
+        let mut sorted_groups = SortedGroups::<i32, i32>::from_pairs(vec![(1, 11)]);
+        assert!(sorted_groups.update(&1, &11, |e| e + 20));
+        assert_eq!(sorted_groups.iter().collect::<Vec<_>>(), vec![(&1, &31)]);
+    }
+
+    #[test]
+    fn test_replace() {
+        #[derive(Debug)]
+        struct Task {
+            id: i32,
+            title: &'static str,
+        }
+
+        impl PartialEq for Task {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+
+        impl Eq for Task {}
+
+        impl PartialOrd for Task {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Task {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.id.cmp(&other.id)
+            }
+        }
+
+        let mut sorted_groups =
+            SortedGroups::<i32, Task>::new(vec![Task { id: 11, title: "a" }], |task| task.id / 10);
+
+        let previous = sorted_groups.replace(Task { id: 11, title: "b" });
+        assert_eq!(previous.map(|task| task.title), Some("a"));
+        assert_eq!(sorted_groups.replace(Task { id: 21, title: "c" }), None);
+        assert_eq!(
+            sorted_groups
+                .iter()
+                .map(|(_, task)| task.title)
+                .collect::<Vec<_>>(),
+            vec!["b", "c"]
+        );
+        assert_eq!(sorted_groups.len(), 2);
+    }
+
+    #[test]
+    fn test_move_element() {
+        let mut sorted_groups = SortedGroups::<&str, &str>::from_pairs(vec![
+            ("today", "laundry"),
+            ("today", "groceries"),
+        ]);
+
+        assert!(sorted_groups.move_element("today", "laundry", "tomorrow"));
+        assert!(!sorted_groups.move_element("today", "laundry", "tomorrow"));
+        assert!(sorted_groups.move_element("today", "groceries", "tomorrow"));
+        assert!(!sorted_groups.contains_group("today"));
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&"tomorrow", &"groceries"), (&"tomorrow", &"laundry")]
+        );
+    }
+
+    #[test]
+    fn test_merge_groups() {
+        let mut sorted_groups = SortedGroups::<&str, i32>::from_pairs(vec![
+            ("earlier", 1),
+            ("yesterday", 2),
+            ("yesterday", 3),
+        ]);
+
+        sorted_groups.merge_groups("yesterday", "earlier");
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&"earlier", &1), (&"earlier", &2), (&"earlier", &3)]
+        );
+
+        sorted_groups.merge_groups("earlier", "archive");
+        assert_eq!(sorted_groups.groups_len(), 1);
+        assert_eq!(sorted_groups.group_len("archive"), 3);
+
+        sorted_groups.merge_groups("missing", "earlier");
+        assert!(!sorted_groups.contains_group("earlier"));
+        assert_eq!(sorted_groups.len(), 3);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 31], |e| e / 10);
+        let mut other = SortedGroups::<i32, i32>::new(vec![12, 11, 21, 41], |e| e / 10);
+
+        sorted_groups.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(other.groups_len(), 0);
+        assert_eq!(sorted_groups.len(), 5);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12), (&2, &21), (&3, &31), (&4, &41)]
+        );
+        assert_eq!(sorted_groups.get(3), Some((&3, &31)));
+    }
+
+    #[test]
+    fn test_merge() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![21], |e| e / 10)
+            .merge(SortedGroups::<i32, i32>::new(vec![11, 22], |e| e / 10));
+
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&2, &21), (&2, &22)]
+        );
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 31], |e| e / 10);
+
+        let mut recent = sorted_groups.split_off(&2);
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12)]
+        );
+        assert_eq!(
+            recent.iter().collect::<Vec<_>>(),
+            vec![(&2, &21), (&3, &31)]
+        );
+        assert_eq!(recent.len(), 2);
+        assert!(recent.insert(22));
+
+        assert!(recent.split_off(&4).is_empty());
+        assert_eq!(recent.split_off(&0).len(), 3);
+        assert!(recent.is_empty());
+    }
+
+    #[test]
+    fn test_split_at() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21], |e| e / 10);
+
+        let (head, tail) = sorted_groups.clone().split_at(2);
+        assert_eq!(head.iter().collect::<Vec<_>>(), vec![(&1, &11), (&1, &12)]);
+        assert_eq!(tail.iter().collect::<Vec<_>>(), vec![(&1, &13), (&2, &21)]);
+        assert_eq!((head.len(), tail.len()), (2, 2));
+        assert_eq!(tail.get(1), Some((&2, &21)));
+
+        let (head, tail) = sorted_groups.clone().split_at(3);
+        assert_eq!((head.groups_len(), tail.groups_len()), (1, 1));
+
+        let (head, tail) = sorted_groups.split_at(4);
+        assert_eq!((head.len(), tail.len()), (4, 0));
     }
 }
@@ -0,0 +1,133 @@
+//! Compact binary snapshots in MessagePack, enabled by the `msgpack` feature.
+//!
+//! A snapshot starts with a 4 bytes magic number and a version byte, followed by the MessagePack
+//! encoding of the serde representation, see [`to_bytes`](SortedGroups::to_bytes).
+use std::{error::Error, fmt};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{GroupStorage, SortedGroups};
+
+const MAGIC: &[u8; 4] = b"SGRP";
+const VERSION: u8 = 1;
+
+/// Error returned by [`SortedGroups::to_bytes`] and [`SortedGroups::from_bytes`].
+#[derive(Debug)]
+pub enum BytesError {
+    /// The bytes don't start with the magic number of snapshots
+    InvalidHeader,
+    /// The snapshot was written in a format version this version can't read
+    UnsupportedVersion(u8),
+    /// Encoding into MessagePack failed
+    Encode(rmp_serde::encode::Error),
+    /// Decoding from MessagePack failed
+    Decode(rmp_serde::decode::Error),
+}
+
+impl fmt::Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytesError::InvalidHeader => f.write_str("bytes are not a SortedGroups snapshot"),
+            BytesError::UnsupportedVersion(version) => {
+                write!(f, "snapshot version {version} is not supported")
+            }
+            BytesError::Encode(error) => write!(f, "encoding failed: {error}"),
+            BytesError::Decode(error) => write!(f, "decoding failed: {error}"),
+        }
+    }
+}
+
+impl Error for BytesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BytesError::Encode(error) => Some(error),
+            BytesError::Decode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Encodes groups and elements into a versioned binary snapshot, to be decoded with
+    /// [`from_bytes`](Self::from_bytes).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BytesError>
+    where
+        G: Serialize,
+        E: Serialize,
+    {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        rmp_serde::encode::write(&mut bytes, self).map_err(BytesError::Encode)?;
+        Ok(bytes)
+    }
+
+    /// Decodes a snapshot written by [`to_bytes`](Self::to_bytes).
+    ///
+    /// As with deserialization, the result has no grouping function, see
+    /// [`from_pairs`](Self::from_pairs).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BytesError>
+    where
+        G: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let payload = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(BytesError::InvalidHeader)?;
+        let (&version, payload) = payload.split_first().ok_or(BytesError::InvalidHeader)?;
+        if version != VERSION {
+            return Err(BytesError::UnsupportedVersion(version));
+        }
+        rmp_serde::from_slice(payload).map_err(BytesError::Decode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut sorted_groups = SortedGroups::<i32, String>::new(
+            vec![
+                "apple".to_string(),
+                "avocado".to_string(),
+                "banana".to_string(),
+            ],
+            |e| e.len() as i32,
+        );
+        sorted_groups.add_group(1);
+
+        let bytes = sorted_groups.to_bytes().unwrap();
+        assert!(bytes.starts_with(b"SGRP\x01"));
+        assert_eq!(SortedGroups::from_bytes(&bytes).unwrap(), sorted_groups);
+    }
+
+    #[test]
+    fn test_from_bytes_checks_header() {
+        let mut bytes = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10)
+            .to_bytes()
+            .unwrap();
+        assert!(matches!(
+            SortedGroups::<i32, i32>::from_bytes(&bytes[..2]),
+            Err(BytesError::InvalidHeader)
+        ));
+
+        bytes[4] = 2;
+        assert!(matches!(
+            SortedGroups::<i32, i32>::from_bytes(&bytes),
+            Err(BytesError::UnsupportedVersion(2))
+        ));
+
+        bytes[4] = VERSION;
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(
+            SortedGroups::<i32, i32>::from_bytes(&bytes),
+            Err(BytesError::Decode(_))
+        ));
+    }
+}
@@ -0,0 +1,316 @@
+//! Notification of every change to an observer, see [`SortedGroups::set_observer`].
+use crate::{history::History, Change, GroupStorage, SortedGroups};
+
+type ChangeFn<G, E> = dyn FnMut(&Change<&G, &E>) + Send + Sync;
+type BatchFn<G, E> = dyn FnMut(&[Change<&G, &E>]) + Send + Sync;
+
+// Function notified of every change, either one at a time or in batches
+pub(crate) enum Observer<G, E> {
+    Changes(Box<ChangeFn<G, E>>),
+    Batches(Box<BatchFn<G, E>>),
+}
+
+impl<G, E> Observer<G, E> {
+    pub(crate) fn notify(&mut self, change: &Change<&G, &E>) {
+        match self {
+            Observer::Changes(observer) => observer(change),
+            Observer::Batches(observer) => observer(std::slice::from_ref(change)),
+        }
+    }
+
+    // Notifies `changes` made together, in a single call to a batch observer
+    pub(crate) fn notify_batch(&mut self, changes: &[Change<&G, &E>]) {
+        match self {
+            Observer::Changes(observer) => changes.iter().for_each(observer),
+            Observer::Batches(observer) => observer(changes),
+        }
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Sets `observer` to be notified of every change made to groups and elements, replacing the
+    /// previous observer if any.
+    ///
+    /// Changes are notified as they are made, their indices referring to the state at that time,
+    /// so that they can be replayed with [`apply`](SortedGroups::apply). As with
+    /// [`diff`](SortedGroups::diff), a removed group is notified after all its elements and an
+    /// inserted group before its elements.
+    ///
+    /// The observer is not cloned along with the `SortedGroups`.
+    pub fn set_observer(&mut self, observer: impl FnMut(&Change<&G, &E>) + Send + Sync + 'static) {
+        self.observer = Some(Observer::Changes(Box::new(observer)));
+    }
+
+    /// Sets `observer` to be notified of changes in batches, replacing the previous observer if
+    /// any.
+    ///
+    /// The changes committed by a [`transaction`](SortedGroups::transaction) are notified as a
+    /// single batch, in the order they were made. Every other change is notified on its own, as
+    /// with [`set_observer`](Self::set_observer).
+    pub fn set_batch_observer(
+        &mut self,
+        observer: impl FnMut(&[Change<&G, &E>]) + Send + Sync + 'static,
+    ) {
+        self.observer = Some(Observer::Batches(Box::new(observer)));
+    }
+
+    /// Removes the observer set with [`set_observer`](Self::set_observer) or
+    /// [`set_batch_observer`](Self::set_batch_observer).
+    pub fn remove_observer(&mut self) {
+        self.observer = None;
+    }
+
+    // Whether changes are notified to an observer, summaries, history or indexes, in which case
+    // mutations go through the primitives notifying them
+    pub(crate) fn is_observed(&self) -> bool {
+        self.observer.is_some()
+            || self.summaries.is_some()
+            || self.history.is_some()
+            || !self.indexes.is_empty()
+    }
+
+    // Notifies the insertion or removal of the group at `index`
+    pub(crate) fn notify_group(&mut self, index: usize, inserted: bool) {
+        if let Some(summaries) = &mut self.summaries {
+            if inserted {
+                summaries.insert_group(index);
+            } else {
+                summaries.remove_group(index);
+            }
+        }
+        let group = &self.groups[index].0;
+        let change = if inserted {
+            Change::GroupInserted { group, index }
+        } else {
+            Change::GroupRemoved { group, index }
+        };
+        notify(&mut self.observer, &mut self.history, &change);
+    }
+
+    // Notifies the insertion or removal of the element at `rank` in the group at `group_index`
+    pub(crate) fn notify_element(&mut self, group_index: usize, rank: usize, inserted: bool) {
+        if !self.is_observed() {
+            return;
+        }
+        let index = self.offset(group_index) + rank;
+        let (group, elements) = &self.groups[group_index];
+        let Some(element) = elements.nth(rank) else {
+            return;
+        };
+        if let Some(summaries) = &mut self.summaries {
+            summaries.update(group_index, element, inserted);
+        }
+        for (_, index) in &mut self.indexes {
+            index.update(group, element, inserted);
+        }
+        let change = if inserted {
+            Change::ElementInserted {
+                group,
+                element,
+                index,
+            }
+        } else {
+            Change::ElementRemoved {
+                group,
+                element,
+                index,
+            }
+        };
+        notify(&mut self.observer, &mut self.history, &change);
+    }
+
+    // Notifies the removal of the elements of the group at `group_index` whose rank matches
+    // `removed`, from the last one
+    pub(crate) fn notify_removals(
+        &mut self,
+        group_index: usize,
+        mut removed: impl FnMut(usize) -> bool,
+    ) {
+        if !self.is_observed() {
+            return;
+        }
+        let offset = self.offset(group_index);
+        let (group, elements) = &self.groups[group_index];
+        for (rank, element) in (0..elements.len()).rev().zip(elements.iter().rev()) {
+            if !removed(rank) {
+                continue;
+            }
+            if let Some(summaries) = &mut self.summaries {
+                summaries.update(group_index, element, false);
+            }
+            for (_, index) in &mut self.indexes {
+                index.update(group, element, false);
+            }
+            notify(
+                &mut self.observer,
+                &mut self.history,
+                &Change::ElementRemoved {
+                    group,
+                    element,
+                    index: offset + rank,
+                },
+            );
+        }
+    }
+
+    // Calls `f` on every element as `retain` does if changes are observed, and notifies the
+    // removal of the elements and groups `retain` would remove. Returns whether each element is
+    // kept, in iteration order
+    pub(crate) fn notify_retain(&mut self, mut f: impl FnMut(&G, &E) -> bool) -> Option<Vec<bool>> {
+        if !self.is_observed() {
+            return None;
+        }
+        let kept = self
+            .groups
+            .iter()
+            .map(|(group, elements)| {
+                elements
+                    .iter()
+                    .map(|element| f(group, element))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for (group_index, group_kept) in kept.iter().enumerate().rev() {
+            self.notify_removals(group_index, |rank| !group_kept[rank]);
+            let removed = group_kept.iter().filter(|kept| !**kept).count();
+            if !self.keep_empty_groups && removed > 0 && removed == group_kept.len() {
+                self.notify_group(group_index, false);
+            }
+        }
+        Some(kept.into_iter().flatten().collect())
+    }
+}
+
+// Notifies `change` to the observer and history, taking them apart from the `SortedGroups` the
+// change borrows from
+pub(crate) fn notify<G, E>(
+    observer: &mut Option<Observer<G, E>>,
+    history: &mut Option<History<G, E>>,
+    change: &Change<&G, &E>,
+) {
+    if let Some(history) = history {
+        history.record(change);
+    }
+    if let Some(observer) = observer {
+        observer.notify(change);
+    }
+}
+
+// Notifies `changes` made together to the observer and history, as a single batch and undo
+// step
+pub(crate) fn notify_batch<G, E>(
+    observer: &mut Option<Observer<G, E>>,
+    history: &mut Option<History<G, E>>,
+    changes: &[Change<&G, &E>],
+) {
+    if let Some(history) = history {
+        history.record_batch(changes);
+    }
+    if let Some(observer) = observer {
+        observer.notify_batch(changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Change, SortedGroups};
+
+    // Replays the changes notified while running `mutate` onto a copy, checking both end up equal
+    fn assert_replayable(
+        mut sorted_groups: SortedGroups<i32, i32>,
+        mutate: impl FnOnce(&mut SortedGroups<i32, i32>),
+    ) {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut replica = sorted_groups.clone();
+        sorted_groups.set_observer({
+            let changes = changes.clone();
+            move |change| changes.lock().unwrap().push(change.cloned())
+        });
+
+        mutate(&mut sorted_groups);
+        let changes = std::mem::take(&mut *changes.lock().unwrap());
+        assert_eq!(replica.apply(changes), Ok(()));
+        assert_eq!(replica, sorted_groups);
+    }
+
+    fn sorted_groups() -> SortedGroups<i32, i32> {
+        SortedGroups::new(vec![11, 12, 13, 21, 31, 32], |e| e / 10)
+    }
+
+    #[test]
+    fn test_observer_notifies_changes() {
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        sorted_groups.set_observer({
+            let changes = changes.clone();
+            move |change| changes.lock().unwrap().push(change.cloned())
+        });
+
+        sorted_groups.insert(21);
+        sorted_groups.remove(&1, &11);
+        assert_eq!(
+            *changes.lock().unwrap(),
+            vec![
+                Change::GroupInserted { group: 2, index: 1 },
+                Change::ElementInserted {
+                    group: 2,
+                    element: 21,
+                    index: 1
+                },
+                Change::ElementRemoved {
+                    group: 1,
+                    element: 11,
+                    index: 0
+                },
+                Change::GroupRemoved { group: 1, index: 0 },
+            ]
+        );
+
+        sorted_groups.remove_observer();
+        sorted_groups.insert(22);
+        assert_eq!(changes.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_observer_changes_are_replayable() {
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            sorted_groups.insert(22);
+            sorted_groups.add_group(5);
+            sorted_groups.update(&1, &11, |e| e + 30);
+            sorted_groups.move_element(&3, &31, 5);
+            sorted_groups.replace(12);
+            sorted_groups.pop_first();
+            sorted_groups.pop_last();
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            sorted_groups.retain(|_, e| e % 2 == 1);
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            assert_eq!(sorted_groups.extract_if(|_, e| e % 10 == 1).len(), 3);
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            sorted_groups.merge_groups(&1, 3);
+            sorted_groups.clear_group(&2);
+            sorted_groups.append(&mut SortedGroups::new(vec![14, 41], |e| e / 10));
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            sorted_groups.entry(4).or_default().insert(41);
+            sorted_groups.entry(1).or_default().take(&12);
+            sorted_groups.split_off(&3);
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            assert_eq!(sorted_groups.drain().len(), 6);
+        });
+        assert_replayable(sorted_groups(), |sorted_groups| {
+            let (head, _) =
+                std::mem::replace(sorted_groups, SortedGroups::from_pairs([])).split_at(2);
+            *sorted_groups = head;
+        });
+    }
+}
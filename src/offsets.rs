@@ -0,0 +1,88 @@
+//! Order-statistic index over group sizes, giving group offsets in logarithmic time.
+
+// Fenwick tree over the number of elements of each group: the node at position `i` holds the
+// sizes of the groups in `(i & (i + 1))..=i`
+#[derive(Clone, Debug)]
+pub(crate) struct GroupOffsets {
+    tree: Vec<usize>,
+}
+
+impl GroupOffsets {
+    pub(crate) fn new(sizes: impl Iterator<Item = usize>) -> Self {
+        let mut tree = sizes.collect::<Vec<_>>();
+        for i in 0..tree.len() {
+            let parent = i | (i + 1);
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+        Self { tree }
+    }
+
+    // Flat index of the first element of the group at `index`, which is the number of elements
+    // if `index` is the number of groups
+    pub(crate) fn offset(&self, index: usize) -> usize {
+        let mut offset = 0;
+        let mut end = index;
+        while end > 0 {
+            offset += self.tree[end - 1];
+            end &= end - 1;
+        }
+        offset
+    }
+
+    // Accounts for `delta` elements inserted in or removed from the group at `index`
+    pub(crate) fn resize(&mut self, index: usize, delta: isize) {
+        let mut i = index;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i].wrapping_add_signed(delta);
+            i |= i + 1;
+        }
+    }
+
+    // Position of the group containing the element at the flat `index`, along with the offset of
+    // the element in that group. `index` must be lower than the number of elements
+    pub(crate) fn locate(&self, index: usize) -> (usize, usize) {
+        // Finds the last group whose offset is lower than or equal to `index`, which skips empty
+        // groups sharing their offset with the next one
+        let mut position = 0;
+        let mut remaining = index;
+        let mut step = (self.tree.len() + 1).next_power_of_two() / 2;
+        while step > 0 {
+            if position + step <= self.tree.len() && self.tree[position + step - 1] <= remaining {
+                position += step;
+                remaining -= self.tree[position - 1];
+            }
+            step /= 2;
+        }
+        (position, remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_offsets() {
+        let sizes = [2, 0, 3, 1, 0, 0, 4];
+        let mut offsets = GroupOffsets::new(sizes.into_iter());
+
+        assert_eq!(
+            (0..=sizes.len())
+                .map(|i| offsets.offset(i))
+                .collect::<Vec<_>>(),
+            vec![0, 2, 2, 5, 6, 6, 6, 10]
+        );
+        assert_eq!(offsets.locate(0), (0, 0));
+        assert_eq!(offsets.locate(2), (2, 0));
+        assert_eq!(offsets.locate(5), (3, 0));
+        assert_eq!(offsets.locate(9), (6, 3));
+
+        offsets.resize(1, 1);
+        offsets.resize(6, -2);
+        assert_eq!(offsets.locate(2), (1, 0));
+        assert_eq!(offsets.offset(7), 9);
+        assert_eq!(offsets.locate(8), (6, 1));
+    }
+}
@@ -0,0 +1,99 @@
+//! Pagination over groups and elements, see [`SortedGroups::page`].
+use crate::SortedGroups;
+
+/// Position a [`SortedGroups::page`] resumes from, serializable with the `serde` feature.
+///
+/// The cursor holds the last element of the previous page and its group, so it stays valid when
+/// elements are inserted or removed in between.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageCursor<G, E> {
+    pub(crate) group: G,
+    pub(crate) element: E,
+}
+
+/// Page of elements, see [`SortedGroups::page`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page<'a, G, E> {
+    /// Elements of the page along with their group, in iteration order
+    pub items: Vec<(&'a G, &'a E)>,
+    /// Groups the elements of the page belong to, in the order of groups
+    pub groups: Vec<&'a G>,
+    /// Cursor to get the next page with, `None` if this page is the last one
+    pub next_cursor: Option<PageCursor<G, E>>,
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Returns the page of at most `size` elements following `cursor`, or the first page if
+    /// `cursor` is `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn page(&self, cursor: Option<PageCursor<G, E>>, size: usize) -> Page<'_, G, E> {
+        assert!(size != 0, "page size must be non-zero");
+        // One more element tells whether there is a next page
+        let mut items = match &cursor {
+            Some(cursor) => self
+                .iter_after(&cursor.group, &cursor.element)
+                .take(size + 1)
+                .collect::<Vec<_>>(),
+            None => self.iter().take(size + 1).collect(),
+        };
+        let next_cursor = if items.len() > size {
+            items.truncate(size);
+            items.last().map(|&(group, element)| PageCursor {
+                group: group.clone(),
+                element: element.clone(),
+            })
+        } else {
+            None
+        };
+        let mut groups = items.iter().map(|&(group, _)| group).collect::<Vec<_>>();
+        groups.dedup();
+        Page {
+            items,
+            groups,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_page() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21, 31], |e| e / 10);
+
+        let page = sorted_groups.page(None, 2);
+        assert_eq!(page.items, vec![(&1, &11), (&1, &12)]);
+        assert_eq!(page.groups, vec![&1]);
+        let cursor = page.next_cursor.unwrap();
+
+        let page = sorted_groups.page(Some(cursor.clone()), 2);
+        assert_eq!(page.items, vec![(&1, &13), (&2, &21)]);
+        assert_eq!(page.groups, vec![&1, &2]);
+
+        sorted_groups.remove(&1, &12);
+        let page = sorted_groups.page(Some(cursor), 3);
+        assert_eq!(page.items, vec![(&1, &13), (&2, &21), (&3, &31)]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_page_descending_groups() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31], |e| e / 10).reverse_groups();
+
+        let page = sorted_groups.page(None, 2);
+        assert_eq!(page.items, vec![(&3, &31), (&2, &21)]);
+        let page = sorted_groups.page(page.next_cursor, 2);
+        assert_eq!(page.items, vec![(&2, &22), (&1, &11)]);
+        assert_eq!(page.groups, vec![&2, &1]);
+    }
+}
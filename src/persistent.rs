@@ -0,0 +1,310 @@
+//! Immutable variant sharing the elements of groups between versions, see
+//! [`PersistentSortedGroups`].
+use std::{borrow::Borrow, collections::BTreeSet, fmt, sync::Arc};
+
+use crate::{GroupFn, SortedGroups};
+
+/// Immutable [`SortedGroups`] whose mutations return a new version, e.g. to keep an undo stack.
+///
+/// Sharing is per group only: each group holds its elements behind an `Arc`, shared by the
+/// versions that didn't modify it, but the list of groups itself is not a persistent structure.
+/// Cloning is `O(1)`, while a mutation copies the whole list of groups, one key and one `Arc`
+/// per group, along with the elements of the single group it modifies, so each version costs
+/// `O(groups)` plus the size of that group. Groups left empty are dropped.
+pub struct PersistentSortedGroups<G, E> {
+    // Groups sorted by key, copied by every mutation, the elements of each group being shared
+    // with the versions that didn't modify it
+    groups: Arc<Vec<(G, Arc<BTreeSet<E>>)>>,
+    group_from_element: Option<GroupFn<E, G>>,
+    len: usize,
+    descending_groups: bool,
+}
+
+impl<G, E> PersistentSortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Returns a version with `element` inserted in its group, creating the group if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`SortedGroups::from_pairs`].
+    pub fn insert(&self, element: E) -> Self {
+        let group_from_element = self
+            .group_from_element
+            .as_ref()
+            .expect("PersistentSortedGroups has no grouping function, use insert_with_group");
+        self.insert_with_group(group_from_element(&element), element)
+    }
+
+    /// Returns a version with `element` inserted in `group`, bypassing the grouping function.
+    ///
+    /// The version shares everything with `self` if it already contains the element.
+    pub fn insert_with_group(&self, group: G, element: E) -> Self {
+        match self.group_index(&group) {
+            Ok(index) if self.groups[index].1.contains(&element) => self.clone(),
+            Ok(index) => self.with_group(index, |elements| {
+                elements.insert(element);
+            }),
+            Err(index) => {
+                let mut groups = Vec::clone(&self.groups);
+                groups.insert(index, (group, Arc::new(BTreeSet::from([element]))));
+                self.with_groups(groups, self.len + 1)
+            }
+        }
+    }
+
+    /// Returns a version without `element` in `group`, which shares everything with `self` if
+    /// there was no such element.
+    pub fn remove<Q, R>(&self, group: &Q, element: &R) -> Self
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.group_index(group) {
+            Ok(index) if self.groups[index].1.contains(element) => {
+                if self.groups[index].1.len() == 1 {
+                    self.without_group(index)
+                } else {
+                    self.with_group(index, |elements| {
+                        elements.remove(element);
+                    })
+                }
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns a version without `group` and its elements.
+    pub fn remove_group<Q>(&self, group: &Q) -> Self
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.group_index(group) {
+            Ok(index) => self.without_group(index),
+            Err(_) => self.clone(),
+        }
+    }
+
+    /// Returns a mutable copy of the current version.
+    pub fn to_sorted_groups(&self) -> SortedGroups<G, E> {
+        let groups = self
+            .groups
+            .iter()
+            .map(|(group, elements)| (group.clone(), BTreeSet::clone(elements)))
+            .collect();
+        let mut sorted_groups = SortedGroups::from_parts(groups, self.group_from_element.clone());
+        sorted_groups.descending_groups = self.descending_groups;
+        sorted_groups
+    }
+
+    // Version where the group at `index` is copied and modified by `f`, which changes its
+    // number of elements by one
+    fn with_group(&self, index: usize, f: impl FnOnce(&mut BTreeSet<E>)) -> Self {
+        let mut groups = Vec::clone(&self.groups);
+        let elements = Arc::make_mut(&mut groups[index].1);
+        let before = elements.len();
+        f(elements);
+        let len = self.len + elements.len() - before;
+        self.with_groups(groups, len)
+    }
+
+    fn without_group(&self, index: usize) -> Self {
+        let mut groups = Vec::clone(&self.groups);
+        let (_, elements) = groups.remove(index);
+        self.with_groups(groups, self.len - elements.len())
+    }
+
+    fn with_groups(&self, groups: Vec<(G, Arc<BTreeSet<E>>)>, len: usize) -> Self {
+        Self {
+            groups: Arc::new(groups),
+            group_from_element: self.group_from_element.clone(),
+            len,
+            descending_groups: self.descending_groups,
+        }
+    }
+}
+
+impl<G, E> PersistentSortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn groups_len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn contains_group<Q>(&self, group: &Q) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_index(group).is_ok()
+    }
+
+    pub fn contains<Q, R>(&self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.group_index(group)
+            .is_ok_and(|index| self.groups[index].1.contains(element))
+    }
+
+    /// Returns an iterator over the elements of `group`, which is empty if the group doesn't
+    /// exist.
+    pub fn iter_group<Q>(&self, group: &Q) -> impl DoubleEndedIterator<Item = &E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.group_index(group)
+            .ok()
+            .map(|index| self.groups[index].1.iter())
+            .into_iter()
+            .flatten()
+    }
+
+    /// Returns an iterator over groups and elements, in the order of groups and then by element.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&G, &E)> {
+        self.groups
+            .iter()
+            .flat_map(|(group, elements)| elements.iter().map(move |element| (group, element)))
+    }
+
+    /// Returns whether `self` and `other` are the same version, or versions sharing all groups.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.groups, &other.groups)
+    }
+
+    fn group_index<Q>(&self, group: &Q) -> Result<usize, usize>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.groups.binary_search_by(|(g, _)| {
+            let ordering = g.borrow().cmp(group);
+            if self.descending_groups {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+    }
+}
+
+impl<G, E> Clone for PersistentSortedGroups<G, E> {
+    fn clone(&self) -> Self {
+        Self {
+            groups: self.groups.clone(),
+            group_from_element: self.group_from_element.clone(),
+            len: self.len,
+            descending_groups: self.descending_groups,
+        }
+    }
+}
+
+impl<G, E> From<SortedGroups<G, E>> for PersistentSortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Converts into the first version, sharing the grouping function and dropping empty groups.
+    fn from(sorted_groups: SortedGroups<G, E>) -> Self {
+        let group_from_element = sorted_groups.group_from_element.clone();
+        let len = sorted_groups.len;
+        let descending_groups = sorted_groups.descending_groups;
+        let groups = sorted_groups
+            .groups
+            .into_iter()
+            .filter(|(_, elements)| !elements.is_empty())
+            .map(|(group, elements)| (group, Arc::new(elements)))
+            .collect();
+        Self {
+            groups: Arc::new(groups),
+            group_from_element,
+            len,
+            descending_groups,
+        }
+    }
+}
+
+impl<G, E> PartialEq for PersistentSortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.groups == other.groups
+    }
+}
+
+impl<G, E> Eq for PersistentSortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+}
+
+impl<G, E> fmt::Debug for PersistentSortedGroups<G, E>
+where
+    G: fmt::Debug,
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.groups
+                    .iter()
+                    .map(|(group, elements)| (group, elements)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_versions_share_structure() {
+        let v1 =
+            PersistentSortedGroups::from(SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| {
+                e / 10
+            }));
+        let v2 = v1.insert(13);
+        let v3 = v2.remove(&2, &21);
+
+        assert_eq!(
+            v1.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &12), (&2, &21)]
+        );
+        assert_eq!(v2.len(), 4);
+        assert_eq!(v3.iter_group(&1).collect::<Vec<_>>(), vec![&11, &12, &13]);
+        assert!(!v3.contains_group(&2));
+        // Only the modified group is copied
+        assert!(Arc::ptr_eq(&v1.groups[1].1, &v2.groups[1].1));
+        assert!(Arc::ptr_eq(&v2.groups[0].1, &v3.groups[0].1));
+        assert!(v2.insert(13).ptr_eq(&v2));
+        assert!(v3.remove_group(&5).ptr_eq(&v3));
+
+        assert!(v3.to_sorted_groups().insert(31));
+        assert_eq!(v1.insert(13).remove(&2, &21), v3);
+    }
+}
@@ -0,0 +1,83 @@
+//! Proptest strategies, enabled by the `proptest` feature.
+use std::{fmt, sync::Arc};
+
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    collection::{vec, SizeRange},
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord + fmt::Debug + 'static,
+    E: fmt::Debug + 'static,
+    S: GroupStorage<E> + fmt::Debug + 'static,
+{
+    /// Returns a strategy generating a `SortedGroups` from `size` elements generated by
+    /// `element`, grouped by `group_from_element` as with [`new`](SortedGroups::new).
+    ///
+    /// Unlike the [`Arbitrary`] implementation, the generated instances keep their grouping
+    /// function, so that elements can be inserted with [`insert`](SortedGroups::insert).
+    pub fn strategy(
+        element: impl Strategy<Value = E> + 'static,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+        size: impl Into<SizeRange>,
+    ) -> BoxedStrategy<Self> {
+        let group_from_element = Arc::new(group_from_element);
+        vec(element, size)
+            .prop_map(move |elements| {
+                let group_from_element = group_from_element.clone();
+                Self::new_with_storage(elements, move |element| group_from_element(element))
+            })
+            .boxed()
+    }
+}
+
+/// Generates a `SortedGroups` from arbitrary `(group, element)` pairs, as
+/// [`from_pairs`](SortedGroups::from_pairs) does, so no grouping function is available.
+///
+/// The parameters are the range of the number of pairs, along with those of the pairs.
+impl<G, E, S> Arbitrary for SortedGroups<G, E, S>
+where
+    G: Arbitrary + Ord + 'static,
+    E: Arbitrary + 'static,
+    S: GroupStorage<E> + fmt::Debug + 'static,
+{
+    type Parameters = (SizeRange, <(G, E) as Arbitrary>::Parameters);
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((size, parameters): Self::Parameters) -> Self::Strategy {
+        vec(any_with::<(G, E)>(parameters), size)
+            .prop_map(Self::from_pairs_with_storage)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::SortedGroups;
+
+    proptest! {
+        #[test]
+        fn test_arbitrary(sorted_groups in any::<SortedGroups<u8, u16>>()) {
+            prop_assert_eq!(sorted_groups.len(), sorted_groups.iter().count());
+            prop_assert!(sorted_groups
+                .iter_groups()
+                .all(|(_, elements)| !elements.is_empty()));
+        }
+
+        #[test]
+        fn test_strategy(
+            mut sorted_groups in SortedGroups::<i32, i32>::strategy(0..100, |e| e / 10, 0..20)
+        ) {
+            prop_assert!(sorted_groups.len() < 20);
+            prop_assert!(sorted_groups.iter().all(|(group, element)| *group == element / 10));
+            sorted_groups.insert(100);
+            prop_assert!(sorted_groups.contains(&10, &100));
+        }
+    }
+}
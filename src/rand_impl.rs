@@ -0,0 +1,96 @@
+//! Random sampling of elements, behind the `rand` feature.
+use rand::{seq::index, Rng};
+
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns `k` elements picked at random without replacement, along with their groups, in
+    /// iteration order.
+    ///
+    /// All elements are returned if there are fewer than `k`.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<(&G, &E)> {
+        pick(self.iter(), self.len, rng, k)
+    }
+
+    /// Returns, for every group, `k` of its elements picked at random without replacement, in
+    /// order.
+    ///
+    /// All elements of a group are returned if it has fewer than `k`. Groups without elements
+    /// are skipped.
+    pub fn sample_per_group<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<(&G, Vec<&E>)> {
+        self.groups
+            .iter()
+            .filter(|(_, elements)| !elements.is_empty())
+            .map(|(group, elements)| (group, pick(elements.iter(), elements.len(), rng, k)))
+            .collect()
+    }
+}
+
+// Picks `k` items of `iter`, which has `len` items, keeping their order
+fn pick<T, R: Rng + ?Sized>(
+    mut iter: impl Iterator<Item = T>,
+    len: usize,
+    rng: &mut R,
+    k: usize,
+) -> Vec<T> {
+    let mut indices = index::sample(rng, len, k.min(len)).into_vec();
+    indices.sort_unstable();
+    let mut next = 0;
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let item = iter.nth(index - next);
+            next = index + 1;
+            item
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::SortedGroups;
+
+    fn sorted_groups() -> SortedGroups<i32, i32> {
+        SortedGroups::new(vec![11, 12, 13, 14, 21, 22, 31], |e| e / 10)
+    }
+
+    #[test]
+    fn test_sample() {
+        let sorted_groups = sorted_groups();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let sample = sorted_groups.sample(&mut rng, 4);
+        assert_eq!(sample.len(), 4);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(sample
+            .iter()
+            .all(|(group, element)| sorted_groups.contains(group, element)));
+        assert_eq!(
+            sorted_groups.sample(&mut rng, 10),
+            sorted_groups.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_sample_per_group() {
+        let mut sorted_groups = sorted_groups();
+        sorted_groups.add_group(4);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let sample = sorted_groups.sample_per_group(&mut rng, 2);
+        let sizes = sample
+            .iter()
+            .map(|(group, elements)| (**group, elements.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![(1, 2), (2, 2), (3, 1)]);
+        assert!(sample
+            .iter()
+            .all(|(group, elements)| elements.iter().all(|element| *element / 10 == **group)));
+    }
+}
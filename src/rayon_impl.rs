@@ -0,0 +1,176 @@
+//! Parallel iterators, enabled by the `rayon` feature.
+use std::{
+    collections::{btree_map, BTreeMap, BTreeSet},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use rayon::iter::{
+    plumbing::UnindexedConsumer, IndexedParallelIterator, IntoParallelIterator,
+    IntoParallelRefIterator, ParallelIterator,
+};
+
+use crate::{Group, GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord + Sync,
+    E: Sync,
+    S: GroupStorage<E> + Sync,
+{
+    /// Returns a parallel iterator over groups and elements.
+    pub fn par_iter(&self) -> SortedGroupsParIter<'_, G, E, S> {
+        SortedGroupsParIter {
+            groups: &self.groups,
+            _elements: PhantomData,
+        }
+    }
+
+    /// Returns a parallel iterator over groups and their elements.
+    pub fn par_iter_groups(&self) -> impl IndexedParallelIterator<Item = (&G, Group<'_, E, S>)> {
+        self.groups
+            .par_iter()
+            .map(|(group, elements)| (group, Group::new(elements)))
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Send,
+    E: Ord + Send,
+{
+    /// Creates a new `SortedGroups` from `elements` in parallel, see [`new`](SortedGroups::new).
+    ///
+    /// Elements are grouped by shards, which are then merged.
+    pub fn from_par_iter(
+        elements: impl IntoParallelIterator<Item = E>,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+    ) -> Self {
+        let groups = elements
+            .into_par_iter()
+            .fold(BTreeMap::<G, BTreeSet<E>>::new, |mut groups, element| {
+                groups
+                    .entry(group_from_element(&element))
+                    .or_default()
+                    .insert(element);
+                groups
+            })
+            .reduce(BTreeMap::new, merge_groups);
+        let mut sorted_groups = Self::from_parts(
+            groups.into_iter().collect(),
+            Some(Arc::new(group_from_element)),
+        );
+        sorted_groups.debug_follow_grouping();
+        sorted_groups
+    }
+}
+
+// Moves the groups of the smaller shard into the larger one
+fn merge_groups<G: Ord, E: Ord>(
+    mut left: BTreeMap<G, BTreeSet<E>>,
+    mut right: BTreeMap<G, BTreeSet<E>>,
+) -> BTreeMap<G, BTreeSet<E>> {
+    if left.len() < right.len() {
+        std::mem::swap(&mut left, &mut right);
+    }
+    for (group, mut elements) in right {
+        match left.entry(group) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(elements);
+            }
+            btree_map::Entry::Occupied(mut entry) => entry.get_mut().append(&mut elements),
+        }
+    }
+    left
+}
+
+/// Parallel iterator over groups and elements of a [`SortedGroups`].
+pub struct SortedGroupsParIter<'a, G, E, S = BTreeSet<E>> {
+    groups: &'a [(G, S)],
+    _elements: PhantomData<&'a E>,
+}
+
+impl<'a, G, E, S> ParallelIterator for SortedGroupsParIter<'a, G, E, S>
+where
+    G: Ord + Sync,
+    E: Sync,
+    S: GroupStorage<E> + Sync,
+{
+    type Item = (&'a G, &'a E);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.groups
+            .par_iter()
+            .flat_map_iter(|(group, elements)| elements.iter().map(move |element| (group, element)))
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a, G, E, S> IntoParallelIterator for &'a SortedGroups<G, E, S>
+where
+    G: Ord + Sync,
+    E: Sync,
+    S: GroupStorage<E> + Sync,
+{
+    type Item = (&'a G, &'a E);
+    type Iter = SortedGroupsParIter<'a, G, E, S>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.par_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_par_iter() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(0..1000, |e| e % 7);
+
+        let mut elements = sorted_groups.par_iter().collect::<Vec<_>>();
+        elements.sort();
+        assert_eq!(elements, sorted_groups.iter().collect::<Vec<_>>());
+        assert_eq!(
+            (&sorted_groups)
+                .into_par_iter()
+                .filter(|(group, _)| **group == 0)
+                .count(),
+            143
+        );
+    }
+
+    #[test]
+    fn test_from_par_iter() {
+        let sorted_groups = SortedGroups::<i32, i32>::from_par_iter(0..10_000, |e| e % 7);
+
+        assert_eq!(sorted_groups, SortedGroups::new(0..10_000, |e| e % 7));
+        assert_eq!(sorted_groups.group_of(&8), Some(1));
+    }
+
+    #[test]
+    fn test_par_iter_groups() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(0..1000, |e| e % 7);
+
+        assert_eq!(
+            sorted_groups
+                .par_iter_groups()
+                .map(|(group, elements)| (*group, elements.len()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 143),
+                (1, 143),
+                (2, 143),
+                (3, 143),
+                (4, 143),
+                (5, 143),
+                (6, 142)
+            ]
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Zero-copy archives, enabled by the `rkyv` feature.
+//!
+//! A `SortedGroups` is archived as a sequence of groups, each with the flat offset of its first
+//! element and its elements in ascending order, so that an [`ArchivedSortedGroups`] can be
+//! queried in place. Groups are archived in their order, descending groups included.
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use rkyv::{
+    bytecheck::CheckBytes,
+    munge::munge,
+    primitive::ArchivedUsize,
+    rancor::Fallible,
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Portable, Serialize,
+};
+
+use crate::SortedGroups;
+
+/// Archived [`SortedGroups`] of archived groups `G` and elements `E`, queried without
+/// deserializing.
+///
+/// Lookups take native keys comparable to the archived ones, such as `i32` for archived `i32`
+/// or `str` for archived `String`. Keys that can't be compared are treated as greater.
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[rkyv(crate = rkyv)]
+#[repr(C)]
+pub struct ArchivedSortedGroups<G, E> {
+    groups: ArchivedVec<ArchivedGroup<G, E>>,
+    len: ArchivedUsize,
+    descending_groups: bool,
+}
+
+// Archived group, along with the flat offset of its first element
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[rkyv(crate = rkyv)]
+#[repr(C)]
+struct ArchivedGroup<G, E> {
+    group: G,
+    offset: ArchivedUsize,
+    elements: ArchivedVec<E>,
+}
+
+impl<G, E> ArchivedSortedGroups<G, E> {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.len.to_native() as usize
+    }
+
+    /// Returns whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of groups.
+    pub fn groups_len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns an iterator over groups and elements.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&G, &E)> {
+        self.iter_groups()
+            .flat_map(|(group, elements)| elements.iter().map(move |element| (group, element)))
+    }
+
+    /// Returns an iterator over groups and their elements.
+    pub fn iter_groups(&self) -> impl ExactSizeIterator<Item = (&G, &[E])> + DoubleEndedIterator {
+        self.groups
+            .iter()
+            .map(|group| (&group.group, group.elements.as_slice()))
+    }
+
+    /// Returns the element at the flat `index`, along with its group.
+    ///
+    /// Validating an archive only checks its layout, so inconsistent lengths or offsets make
+    /// lookups return `None` instead of panicking.
+    pub fn get(&self, index: usize) -> Option<(&G, &E)> {
+        if index >= self.len() {
+            return None;
+        }
+        // The last group starting at or before `index` holds it, empty groups sharing the offset
+        // of the next one
+        let group_index = self
+            .groups
+            .partition_point(|group| group.offset.to_native() as usize <= index);
+        let group = &self.groups[group_index.checked_sub(1)?];
+        let rank = index - group.offset.to_native() as usize;
+        Some((&group.group, group.elements.get(rank)?))
+    }
+
+    /// Returns the elements of `group`.
+    pub fn group_elements<Q>(&self, group: &Q) -> Option<&[E]>
+    where
+        G: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        let index = self
+            .groups
+            .binary_search_by(|archived| {
+                let ordering = compare(&archived.group, group);
+                if self.descending_groups {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .ok()?;
+        Some(self.groups[index].elements.as_slice())
+    }
+
+    /// Returns whether `group` exists.
+    pub fn contains_group<Q>(&self, group: &Q) -> bool
+    where
+        G: PartialOrd<Q>,
+        Q: ?Sized,
+    {
+        self.group_elements(group).is_some()
+    }
+
+    /// Returns whether `group` contains `element`.
+    pub fn contains<Q, R>(&self, group: &Q, element: &R) -> bool
+    where
+        G: PartialOrd<Q>,
+        Q: ?Sized,
+        E: PartialOrd<R>,
+        R: ?Sized,
+    {
+        self.group_elements(group).is_some_and(|elements| {
+            elements
+                .binary_search_by(|archived| compare(archived, element))
+                .is_ok()
+        })
+    }
+}
+
+// Compares an archived key with a native one, incomparable keys being greater
+fn compare<T, Q>(archived: &T, key: &Q) -> Ordering
+where
+    T: PartialOrd<Q>,
+    Q: ?Sized,
+{
+    archived.partial_cmp(key).unwrap_or(Ordering::Greater)
+}
+
+/// Resolver of an [`ArchivedSortedGroups`].
+pub struct SortedGroupsResolver {
+    groups: VecResolver,
+}
+
+impl<G, E> Archive for SortedGroups<G, E>
+where
+    G: Ord + Archive,
+    E: Ord + Archive,
+{
+    type Archived = ArchivedSortedGroups<G::Archived, E::Archived>;
+    type Resolver = SortedGroupsResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedSortedGroups { groups, len, descending_groups } = out);
+        ArchivedVec::resolve_from_len(self.groups.len(), resolver.groups, groups);
+        self.len.resolve((), len);
+        self.descending_groups.resolve((), descending_groups);
+    }
+}
+
+impl<G, E, S> Serialize<S> for SortedGroups<G, E>
+where
+    G: Ord + Serialize<S>,
+    E: Ord + Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        let groups = self
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(index, (group, elements))| GroupRef {
+                group,
+                offset: self.offset(index),
+                elements,
+            });
+        Ok(SortedGroupsResolver {
+            groups: ArchivedVec::serialize_from_iter::<GroupRef<'_, G, E>, _, _>(
+                groups, serializer,
+            )?,
+        })
+    }
+}
+
+/// Deserialized instances have no grouping function, as if built with
+/// [`SortedGroups::from_pairs`].
+impl<G, E, D> Deserialize<SortedGroups<G, E>, D> for ArchivedSortedGroups<G::Archived, E::Archived>
+where
+    G: Ord + Archive,
+    G::Archived: Deserialize<G, D>,
+    E: Ord + Archive,
+    E::Archived: Deserialize<E, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<SortedGroups<G, E>, D::Error> {
+        let groups = self
+            .groups
+            .iter()
+            .map(|archived| {
+                let elements = archived
+                    .elements
+                    .iter()
+                    .map(|element| element.deserialize(deserializer))
+                    .collect::<Result<BTreeSet<_>, _>>()?;
+                Ok((archived.group.deserialize(deserializer)?, elements))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut sorted_groups = SortedGroups::from_parts(groups, None);
+        sorted_groups.descending_groups = self.descending_groups;
+        Ok(sorted_groups)
+    }
+}
+
+// Group serialized as an `ArchivedGroup`
+#[derive(Clone, Copy)]
+struct GroupRef<'a, G, E> {
+    group: &'a G,
+    offset: usize,
+    elements: &'a BTreeSet<E>,
+}
+
+struct GroupResolver<G: Archive> {
+    group: G::Resolver,
+    elements: VecResolver,
+}
+
+impl<G, E> Archive for GroupRef<'_, G, E>
+where
+    G: Archive,
+    E: Archive,
+{
+    type Archived = ArchivedGroup<G::Archived, E::Archived>;
+    type Resolver = GroupResolver<G>;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedGroup { group, offset, elements } = out);
+        self.group.resolve(resolver.group, group);
+        self.offset.resolve((), offset);
+        ArchivedVec::resolve_from_len(self.elements.len(), resolver.elements, elements);
+    }
+}
+
+impl<G, E, S> Serialize<S> for GroupRef<'_, G, E>
+where
+    G: Serialize<S>,
+    E: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(GroupResolver {
+            group: self.group.serialize(serializer)?,
+            elements: ArchivedVec::serialize_from_iter::<E, _, _>(
+                self.elements.iter(),
+                serializer,
+            )?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::{primitive::ArchivedUsize, rancor::Error, seal::Seal, vec::ArchivedVec, Archived};
+
+    use crate::SortedGroups;
+
+    fn sorted_groups() -> SortedGroups<String, String> {
+        let mut sorted_groups = SortedGroups::new(
+            vec!["apple", "avocado", "banana", "cherry", "coconut"]
+                .into_iter()
+                .map(String::from),
+            |e| e[..1].to_string(),
+        );
+        sorted_groups.add_group("b0".to_string());
+        sorted_groups
+    }
+
+    #[test]
+    fn test_archived_queries() {
+        let sorted_groups = sorted_groups();
+        let bytes = rkyv::to_bytes::<Error>(&sorted_groups).unwrap();
+        let archived =
+            rkyv::access::<Archived<SortedGroups<String, String>>, Error>(&bytes).unwrap();
+
+        assert_eq!(archived.len(), 5);
+        assert_eq!(archived.groups_len(), 4);
+        assert!(archived
+            .iter()
+            .map(|(group, element)| (group.as_str(), element.as_str()))
+            .eq(sorted_groups
+                .iter()
+                .map(|(group, element)| (group.as_str(), element.as_str()))));
+        for index in 0..6 {
+            assert_eq!(
+                archived
+                    .get(index)
+                    .map(|(group, element)| (group.as_str(), element.as_str())),
+                sorted_groups
+                    .get(index)
+                    .map(|(group, element)| (group.as_str(), element.as_str()))
+            );
+        }
+        assert_eq!(archived.group_elements("b0").map(<[_]>::len), Some(0));
+        assert!(archived.contains("c", "coconut"));
+        assert!(!archived.contains("c", "banana"));
+        assert!(!archived.contains_group("d"));
+    }
+
+    #[test]
+    fn test_archive_round_trip() {
+        let sorted_groups = sorted_groups();
+        let bytes = rkyv::to_bytes::<Error>(&sorted_groups).unwrap();
+        let deserialized = rkyv::from_bytes::<SortedGroups<String, String>, Error>(&bytes).unwrap();
+        assert_eq!(deserialized, sorted_groups);
+    }
+
+    #[test]
+    fn test_archive_descending_groups() {
+        let sorted_groups = sorted_groups().reverse_groups();
+        let bytes = rkyv::to_bytes::<Error>(&sorted_groups).unwrap();
+        let archived =
+            rkyv::access::<Archived<SortedGroups<String, String>>, Error>(&bytes).unwrap();
+        assert_eq!(
+            archived
+                .get(0)
+                .map(|(group, element)| (group.as_str(), element.as_str())),
+            Some(("c", "cherry"))
+        );
+        assert!(archived.contains("a", "avocado"));
+        assert_eq!(archived.group_elements("b0").map(<[_]>::len), Some(0));
+
+        let deserialized = rkyv::from_bytes::<SortedGroups<String, String>, Error>(&bytes).unwrap();
+        assert!(deserialized.descending_groups());
+        assert_eq!(deserialized, sorted_groups);
+    }
+
+    #[test]
+    fn test_get_on_inconsistent_archive() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        let mut bytes = rkyv::to_bytes::<Error>(&sorted_groups).unwrap();
+        {
+            let archived =
+                rkyv::access_mut::<Archived<SortedGroups<i32, i32>>, Error>(&mut bytes).unwrap();
+            // SAFETY: only plain integers are overwritten, keeping the archive valid
+            let archived = unsafe { Seal::unseal_unchecked(archived) };
+            archived.len = ArchivedUsize::from_native(10);
+            let groups = ArchivedVec::as_slice_seal(Seal::new(&mut archived.groups));
+            let groups = unsafe { Seal::unseal_unchecked(groups) };
+            groups[0].offset = ArchivedUsize::from_native(1);
+        }
+
+        let archived = rkyv::access::<Archived<SortedGroups<i32, i32>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 10);
+        assert_eq!(archived.get(0), None);
+        assert_eq!(archived.get(4), None);
+        assert_eq!(archived.get(9), None);
+        assert!(archived.get(2).is_some());
+    }
+}
@@ -0,0 +1,199 @@
+//! Lookups of elements by a key of their own, see [`SortedGroups::add_index`].
+use std::{
+    any::Any,
+    collections::{BTreeSet, HashMap},
+    hash::Hash,
+    sync::Arc,
+};
+
+use crate::SortedGroups;
+
+/// Secondary index along with its name.
+pub(crate) type NamedIndex<G, E> = (&'static str, Box<dyn SecondaryIndex<G, E>>);
+
+// Secondary index, hiding the key type from `SortedGroups`
+pub(crate) trait SecondaryIndex<G, E>: Send + Sync {
+    // Accounts for `element` being inserted in or removed from `group`
+    fn update(&mut self, group: &G, element: &E, inserted: bool);
+
+    fn clone_box(&self) -> Box<dyn SecondaryIndex<G, E>>;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+// Copies of the groups and elements having each key, sorted so that they are removed in
+// logarithmic time
+struct KeyIndex<G, E, K> {
+    key_of: Arc<dyn Fn(&E) -> K + Send + Sync>,
+    entries: HashMap<K, BTreeSet<(G, E)>>,
+}
+
+impl<G, E, K> SecondaryIndex<G, E> for KeyIndex<G, E, K>
+where
+    G: Ord + Clone + Send + Sync + 'static,
+    E: Ord + Clone + Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn update(&mut self, group: &G, element: &E, inserted: bool) {
+        let key = (self.key_of)(element);
+        if inserted {
+            self.entries
+                .entry(key)
+                .or_default()
+                .insert((group.clone(), element.clone()));
+            return;
+        }
+        let Some(entries) = self.entries.get_mut(&key) else {
+            return;
+        };
+        entries.remove(&(group.clone(), element.clone()));
+        if entries.is_empty() {
+            self.entries.remove(&key);
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn SecondaryIndex<G, E>> {
+        Box::new(KeyIndex {
+            key_of: self.key_of.clone(),
+            entries: self.entries.clone(),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone + Send + Sync + 'static,
+    E: Ord + Clone + Send + Sync + 'static,
+{
+    /// Indexes elements by `key`, computed from the current elements and then updated on every
+    /// change, replacing the previous index called `name` if any.
+    ///
+    /// The index holds a clone of each element along with its group, hence `G: Clone` and
+    /// `E: Clone`, so every index takes about as much memory as the elements themselves, and
+    /// each removal clones the removed element and its group to find its entry. Mutations then go
+    /// through each changed element, as when an observer is set, see
+    /// [`set_observer`](Self::set_observer). Indexes are cloned along with the `SortedGroups`,
+    /// but not carried over to the parts split off from it.
+    pub fn add_index<K>(
+        &mut self,
+        name: &'static str,
+        key: impl Fn(&E) -> K + Send + Sync + 'static,
+    ) where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+    {
+        let mut index = KeyIndex {
+            key_of: Arc::new(key),
+            entries: HashMap::new(),
+        };
+        for (group, elements) in &self.groups {
+            for element in elements {
+                index.update(group, element, true);
+            }
+        }
+        self.remove_index(name);
+        self.indexes.push((name, Box::new(index)));
+    }
+
+    /// Drops the index called `name`, returning whether it existed.
+    pub fn remove_index(&mut self, name: &str) -> bool {
+        let len = self.indexes.len();
+        self.indexes.retain(|(index_name, _)| *index_name != name);
+        self.indexes.len() < len
+    }
+
+    /// Returns the element whose `key` is `key` in the index called `name`, along with its group.
+    ///
+    /// If several elements have that key, the one in the least group is returned, the least one
+    /// within that group. `None` is returned if there is no such element, no index called `name`
+    /// or if its keys are not of type `K`.
+    pub fn find_by_index<K>(&self, name: &str, key: &K) -> Option<(&G, &E)>
+    where
+        K: Hash + Eq + Clone + Send + Sync + 'static,
+    {
+        let (_, index) = self
+            .indexes
+            .iter()
+            .find(|(index_name, _)| *index_name == name)?;
+        let index = index.as_any().downcast_ref::<KeyIndex<G, E, K>>()?;
+        let (group, element) = index.entries.get(key)?.first()?;
+        let (group, elements) = &self.groups[self.group_index(group).ok()?];
+        Some((group, elements.get(element)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Task {
+        priority: u8,
+        id: u32,
+    }
+
+    fn task(priority: u8, id: u32) -> Task {
+        Task { priority, id }
+    }
+
+    #[test]
+    fn test_find_by_index_with_shared_keys() {
+        let mut sorted_groups = SortedGroups::<u8, Task>::new(
+            vec![task(2, 1), task(1, 2), task(1, 1), task(3, 1)],
+            |task| task.priority,
+        );
+        sorted_groups.add_index("by_id", |task| task.id % 2);
+
+        assert_eq!(
+            sorted_groups.find_by_index("by_id", &1u32),
+            Some((&1, &task(1, 1)))
+        );
+        sorted_groups.remove(&1, &task(1, 1));
+        assert_eq!(
+            sorted_groups.find_by_index("by_id", &1u32),
+            Some((&2, &task(2, 1)))
+        );
+    }
+
+    #[test]
+    fn test_find_by_index() {
+        let mut sorted_groups =
+            SortedGroups::<u8, Task>::new(vec![task(1, 10), task(2, 20)], |task| task.priority);
+        sorted_groups.add_index("by_id", |task| task.id);
+
+        assert_eq!(
+            sorted_groups.find_by_index("by_id", &20u32),
+            Some((&2, &task(2, 20)))
+        );
+        sorted_groups.insert(task(1, 30));
+        sorted_groups.remove(&2, &task(2, 20));
+        sorted_groups.update(&1, &task(1, 10), |task| Task {
+            priority: 3,
+            ..task
+        });
+
+        assert_eq!(sorted_groups.find_by_index("by_id", &20u32), None);
+        assert_eq!(
+            sorted_groups.find_by_index("by_id", &30u32),
+            Some((&1, &task(1, 30)))
+        );
+        assert_eq!(
+            sorted_groups.find_by_index("by_id", &10u32),
+            Some((&3, &task(3, 10)))
+        );
+        assert_eq!(sorted_groups.find_by_index("by_id", &10u64), None);
+        assert_eq!(
+            sorted_groups
+                .clone()
+                .find_by_index("by_id", &10u32)
+                .map(|(g, _)| *g),
+            Some(3)
+        );
+
+        assert!(sorted_groups.remove_index("by_id"));
+        assert_eq!(sorted_groups.find_by_index("by_id", &10u32), None);
+    }
+}
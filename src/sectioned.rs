@@ -0,0 +1,113 @@
+//! Iteration over groups and elements as a list of sections, each starting with a header.
+use std::collections::BTreeSet;
+
+use crate::{GroupStorage, SortedGroups};
+
+/// Row of a sectioned list, see [`SortedGroups::iter_sectioned`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionItem<'a, G, E> {
+    /// Header of a group, coming before its elements
+    Header(&'a G),
+    /// Element of a group
+    Item(&'a G, &'a E),
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns an iterator over groups and elements, yielding a header before the elements of
+    /// each group, including empty ones.
+    pub fn iter_sectioned(&self) -> SectionedIter<'_, G, E, S> {
+        SectionedIter {
+            groups_iter: self.groups.iter(),
+            current_group: None,
+            remaining: self.sectioned_len(),
+        }
+    }
+
+    /// Returns the number of rows yielded by [`iter_sectioned`](Self::iter_sectioned), that is the
+    /// number of elements and groups.
+    pub fn sectioned_len(&self) -> usize {
+        self.len + self.groups.len()
+    }
+}
+
+pub struct SectionedIter<'a, G, E, S = BTreeSet<E>>
+where
+    E: 'a,
+    S: GroupStorage<E> + 'a,
+{
+    // Iterator over groups whose header is not yet yielded
+    groups_iter: std::slice::Iter<'a, (G, S)>,
+    // Current group and its iterator
+    current_group: Option<(&'a G, S::Iter<'a>)>,
+    // Number of rows left to yield
+    remaining: usize,
+}
+
+impl<'a, G, E, S> Iterator for SectionedIter<'a, G, E, S>
+where
+    S: GroupStorage<E>,
+{
+    type Item = SectionItem<'a, G, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_item = self
+            .current_group
+            .as_mut()
+            .and_then(|(group, iter)| Some(SectionItem::Item(*group, iter.next()?)));
+        let item = match next_item {
+            Some(item) => item,
+            None => {
+                // Current group is exhausted, move to next group
+                let (group, elements) = self.groups_iter.next()?;
+                self.current_group = Some((group, elements.iter()));
+                SectionItem::Header(group)
+            }
+        };
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<G, E, S> ExactSizeIterator for SectionedIter<'_, G, E, S> where S: GroupStorage<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_sectioned() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 31], |e| e / 10);
+        sorted_groups.add_group(2);
+
+        let rows = sorted_groups.iter_sectioned();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows.collect::<Vec<_>>(),
+            vec![
+                SectionItem::Header(&1),
+                SectionItem::Item(&1, &11),
+                SectionItem::Item(&1, &12),
+                SectionItem::Header(&2),
+                SectionItem::Header(&3),
+                SectionItem::Item(&3, &31),
+            ]
+        );
+        assert_eq!(sorted_groups.sectioned_len(), 6);
+    }
+
+    #[test]
+    fn test_iter_sectioned_empty() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![], |e| e / 10);
+
+        assert_eq!(sorted_groups.iter_sectioned().next(), None);
+        assert_eq!(sorted_groups.sectioned_len(), 0);
+    }
+}
@@ -0,0 +1,311 @@
+//! `Serialize` and `Deserialize` implementations, enabled by the `serde` feature.
+//!
+//! A `SortedGroups` is represented as a map of groups to sequences of elements, both in
+//! ascending order. As other settings, descending groups are not serialized.
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{GroupStorage, PageCursor, SortedGroups};
+
+impl<G, E, S> Serialize for SortedGroups<G, E, S>
+where
+    G: Ord + Serialize,
+    E: Serialize,
+    S: GroupStorage<E>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_map(
+            self.groups_ordered(false)
+                .map(|(group, elements)| (group, SerializeElements(elements, PhantomData))),
+        )
+    }
+}
+
+// Elements of a group, serialized as a sequence
+struct SerializeElements<'a, E, S>(&'a S, PhantomData<E>);
+
+impl<E, S> Serialize for SerializeElements<'_, E, S>
+where
+    E: Serialize,
+    S: GroupStorage<E>,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// Deserialized instances have no grouping function, as if built with
+/// [`SortedGroups::from_pairs`].
+///
+/// Deserialization fails if groups or elements are not in strictly ascending order.
+impl<'de, G, E, S> Deserialize<'de> for SortedGroups<G, E, S>
+where
+    G: Ord + Deserialize<'de>,
+    E: Deserialize<'de>,
+    S: GroupStorage<E>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SortedGroupsVisitor(PhantomData))
+    }
+}
+
+struct SortedGroupsVisitor<G, E, S>(PhantomData<(G, E, S)>);
+
+impl<'de, G, E, S> Visitor<'de> for SortedGroupsVisitor<G, E, S>
+where
+    G: Ord + Deserialize<'de>,
+    E: Deserialize<'de>,
+    S: GroupStorage<E>,
+{
+    type Value = SortedGroups<G, E, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of sorted groups to sorted sequences of elements")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut groups = Vec::<(G, S)>::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(group) = map.next_key::<G>()? {
+            if groups
+                .last()
+                .is_some_and(|(previous, _)| previous >= &group)
+            {
+                return Err(de::Error::custom("groups are not in ascending order"));
+            }
+            let elements = map.next_value_seed(GroupElementsSeed(PhantomData))?;
+            groups.push((group, elements));
+        }
+        Ok(SortedGroups::from_parts(groups, None))
+    }
+}
+
+// Elements of a group, pushed into the storage as they are deserialized
+struct GroupElementsSeed<E, S>(PhantomData<(E, S)>);
+
+impl<'de, E, S> DeserializeSeed<'de> for GroupElementsSeed<E, S>
+where
+    E: Deserialize<'de>,
+    S: GroupStorage<E>,
+{
+    type Value = S;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, E, S> Visitor<'de> for GroupElementsSeed<E, S>
+where
+    E: Deserialize<'de>,
+    S: GroupStorage<E>,
+{
+    type Value = S;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sorted sequence of elements")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<S, A::Error> {
+        let mut elements = S::default();
+        while let Some(element) = seq.next_element()? {
+            if elements.push(element).is_err() {
+                return Err(de::Error::custom("elements are not in ascending order"));
+            }
+        }
+        Ok(elements)
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Deserializes a sequence of elements, in any order, grouped by `group_from_element` as
+    /// with [`new`](SortedGroups::new).
+    ///
+    /// Elements are inserted as they are deserialized rather than collected first, so that large
+    /// inputs can be streamed with little memory besides the `SortedGroups` itself. Elements
+    /// coming sorted by group and then by element are appended to the last group with
+    /// [`GroupStorage::push`] instead of being looked up, as with
+    /// [`from_sorted_iter`](SortedGroups::from_sorted_iter), and the others are inserted as
+    /// usual. Unlike deserialized maps, the result keeps its grouping function.
+    pub fn deserialize_elements<'de, D>(
+        deserializer: D,
+        group_from_element: impl Fn(&E) -> G + Send + Sync + 'static,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        E: Deserialize<'de>,
+    {
+        let mut sorted_groups = Self::new_with_storage([], group_from_element);
+        deserializer.deserialize_seq(ElementsVisitor(&mut sorted_groups))?;
+        Ok(sorted_groups)
+    }
+}
+
+// Inserts the elements of a sequence in a `SortedGroups` with a grouping function, appending
+// those that come in order
+struct ElementsVisitor<'a, G, E, S>(&'a mut SortedGroups<G, E, S>)
+where
+    G: Ord,
+    S: GroupStorage<E>;
+
+impl<'de, G, E, S> Visitor<'de> for ElementsVisitor<'_, G, E, S>
+where
+    G: Ord,
+    E: Deserialize<'de>,
+    S: GroupStorage<E>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of elements")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(element) = seq.next_element()? {
+            let group = self
+                .0
+                .group_of(&element)
+                .expect("deserialized elements are grouped by a grouping function");
+            self.0.push_or_insert(group, element);
+        }
+        Ok(())
+    }
+}
+
+/// A cursor is represented as a `(group, element)` tuple.
+impl<G, E> Serialize for PageCursor<G, E>
+where
+    G: Serialize,
+    E: Serialize,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        (&self.group, &self.element).serialize(serializer)
+    }
+}
+
+impl<'de, G, E> Deserialize<'de> for PageCursor<G, E>
+where
+    G: Deserialize<'de>,
+    E: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (group, element) = <(G, E)>::deserialize(deserializer)?;
+        Ok(PageCursor { group, element })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Change, Journal, PageCursor, SortedGroups};
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let sorted_groups = SortedGroups::<String, i32>::from_pairs(vec![
+            ("b".to_string(), 2),
+            ("a".to_string(), 1),
+            ("b".to_string(), 1),
+        ]);
+
+        let json = serde_json::to_string(&sorted_groups).unwrap();
+        assert_eq!(json, r#"{"a":[1],"b":[1,2]}"#);
+        assert_eq!(
+            serde_json::from_str::<SortedGroups<String, i32>>(&json).unwrap(),
+            sorted_groups
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rejects_unsorted_input() {
+        for json in [r#"{"b":[1],"a":[1]}"#, r#"{"a":[2,1]}"#, r#"{"a":[1,1]}"#] {
+            assert!(
+                serde_json::from_str::<SortedGroups<String, i32>>(json).is_err(),
+                "{json} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_elements() {
+        let mut deserializer = serde_json::Deserializer::from_str("[21, 11, 12, 11]");
+        let mut sorted_groups =
+            SortedGroups::<i32, i32>::deserialize_elements(&mut deserializer, |e| e / 10).unwrap();
+        assert_eq!(
+            sorted_groups,
+            SortedGroups::new(vec![11, 12, 21], |e| e / 10)
+        );
+
+        assert!(sorted_groups.insert(31));
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+        assert!(SortedGroups::<i32, i32>::deserialize_elements(
+            &mut serde_json::Deserializer::from_str(r#"{"a":[1]}"#),
+            |e| e / 10
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_deserialize_sorted_elements() {
+        let mut deserializer = serde_json::Deserializer::from_str("[11, 12, 12, 21, 31, 14, 32]");
+        let sorted_groups =
+            SortedGroups::<i32, i32>::deserialize_elements(&mut deserializer, |e| e / 10).unwrap();
+        assert_eq!(
+            sorted_groups,
+            SortedGroups::new(vec![11, 12, 14, 21, 31, 32], |e| e / 10)
+        );
+        assert_eq!(sorted_groups.get(3), Some((&2, &21)));
+        assert_eq!(sorted_groups.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_serialize_empty_group() {
+        let mut sorted_groups = SortedGroups::<String, i32>::from_pairs(vec![("a".to_string(), 1)]);
+        sorted_groups.add_group("b".to_string());
+
+        let json = serde_json::to_string(&sorted_groups).unwrap();
+        assert_eq!(json, r#"{"a":[1],"b":[]}"#);
+        assert_eq!(
+            serde_json::from_str::<SortedGroups<String, i32>>(&json).unwrap(),
+            sorted_groups
+        );
+    }
+
+    #[test]
+    fn test_page_cursor_round_trip() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        let cursor = sorted_groups.page(None, 1).next_cursor.unwrap();
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, "[1,11]");
+        let cursor = serde_json::from_str::<PageCursor<i32, i32>>(&json).unwrap();
+        assert_eq!(sorted_groups.page(Some(cursor), 1).items, vec![(&1, &12)]);
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let journal = Journal::from(vec![
+            Change::GroupInserted { group: 1, index: 0 },
+            Change::ElementInserted {
+                group: 1,
+                element: 11,
+                index: 0,
+            },
+        ]);
+
+        let json = serde_json::to_string(&journal).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"GroupInserted":{"group":1,"index":0}},{"ElementInserted":{"group":1,"element":11,"index":0}}]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Journal<i32, i32>>(&json).unwrap(),
+            journal
+        );
+    }
+}
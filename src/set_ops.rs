@@ -0,0 +1,190 @@
+//! Set operations between two instances, applied group by group.
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::SortedGroups;
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Returns the elements in `self` or `other`.
+    ///
+    /// The result shares the grouping function and settings of `self`, as do the results of the
+    /// other set operations.
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |elements, other_elements| {
+            match (elements, other_elements) {
+                (Some(elements), Some(other_elements)) => {
+                    elements.union(other_elements).cloned().collect()
+                }
+                (Some(elements), None) | (None, Some(elements)) => elements.clone(),
+                (None, None) => BTreeSet::new(),
+            }
+        })
+    }
+
+    /// Returns the elements in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.combine(other, |elements, other_elements| {
+            match (elements, other_elements) {
+                (Some(elements), Some(other_elements)) => {
+                    elements.intersection(other_elements).cloned().collect()
+                }
+                _ => BTreeSet::new(),
+            }
+        })
+    }
+
+    /// Returns the elements in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine(other, |elements, other_elements| {
+            match (elements, other_elements) {
+                (Some(elements), Some(other_elements)) => {
+                    elements.difference(other_elements).cloned().collect()
+                }
+                (Some(elements), None) => elements.clone(),
+                _ => BTreeSet::new(),
+            }
+        })
+    }
+
+    /// Returns the elements in `self` or `other` but not in both.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.combine(other, |elements, other_elements| {
+            match (elements, other_elements) {
+                (Some(elements), Some(other_elements)) => elements
+                    .symmetric_difference(other_elements)
+                    .cloned()
+                    .collect(),
+                (Some(elements), None) | (None, Some(elements)) => elements.clone(),
+                (None, None) => BTreeSet::new(),
+            }
+        })
+    }
+
+    // Walks the groups of both instances in order, combining the elements of groups with the same
+    // key and dropping groups left empty
+    fn combine(
+        &self,
+        other: &Self,
+        f: impl Fn(Option<&BTreeSet<E>>, Option<&BTreeSet<E>>) -> BTreeSet<E>,
+    ) -> Self {
+        let mut groups = Vec::new();
+        // Groups of `other` are walked in the order of those of `self`
+        let mut left = self.groups.iter().peekable();
+        let mut right = other.groups_ordered(self.descending_groups).peekable();
+        loop {
+            let ordering = match (left.peek(), right.peek()) {
+                (Some((left_group, _)), Some((right_group, _))) => {
+                    self.cmp_groups(left_group, right_group)
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let (left_group, right_group) = match ordering {
+                Ordering::Less => (left.next(), None),
+                Ordering::Greater => (None, right.next()),
+                Ordering::Equal => (left.next(), right.next()),
+            };
+            let Some((group, _)) = left_group.or(right_group) else {
+                break;
+            };
+            let elements = f(
+                left_group.map(|(_, elements)| elements),
+                right_group.map(|(_, elements)| elements),
+            );
+            if !elements.is_empty() {
+                groups.push((group.clone(), elements));
+            }
+        }
+        let mut combined = Self::from_parts(groups, self.group_from_element.clone());
+        combined.keep_empty_groups = self.keep_empty_groups;
+        combined.descending_groups = self.descending_groups;
+        combined
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Returns whether every element of `self` is in the same group of `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len <= other.len
+            && self.groups.iter().all(|(group, elements)| {
+                elements.is_empty()
+                    || other
+                        .group_elements(group)
+                        .is_some_and(|other_elements| elements.is_subset(other_elements))
+            })
+    }
+
+    /// Returns whether every element of `other` is in the same group of `self`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns whether `self` and `other` have no element in common within the same group.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.groups.iter().all(|(group, elements)| {
+            other
+                .group_elements(group)
+                .is_none_or(|other_elements| elements.is_disjoint(other_elements))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    fn sorted_groups(elements: Vec<i32>) -> SortedGroups<i32, i32> {
+        SortedGroups::new(elements, |e| e / 10)
+    }
+
+    fn elements(sorted_groups: &SortedGroups<i32, i32>) -> Vec<i32> {
+        sorted_groups.iter().map(|(_, element)| *element).collect()
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = sorted_groups(vec![11, 12, 21, 31]);
+        let b = sorted_groups(vec![12, 13, 31, 41]);
+
+        assert_eq!(elements(&a.union(&b)), vec![11, 12, 13, 21, 31, 41]);
+        assert_eq!(elements(&a.intersection(&b)), vec![12, 31]);
+        assert_eq!(elements(&a.difference(&b)), vec![11, 21]);
+        assert_eq!(elements(&a.symmetric_difference(&b)), vec![11, 13, 21, 41]);
+    }
+
+    #[test]
+    fn test_set_operations_drop_empty_groups() {
+        let a = sorted_groups(vec![11, 21]);
+        let b = sorted_groups(vec![11, 22]);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(intersection.groups_len(), 1);
+        assert_eq!(intersection.len(), 1);
+        assert_eq!(a.difference(&a).groups_len(), 0);
+    }
+
+    #[test]
+    fn test_relations() {
+        let a = sorted_groups(vec![11, 21]);
+        let b = sorted_groups(vec![11, 12, 21]);
+        let c = sorted_groups(vec![12, 22]);
+
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(a.is_disjoint(&c));
+        assert!(!b.is_disjoint(&c));
+
+        let mut empty_group = sorted_groups(vec![]);
+        empty_group.add_group(5);
+        assert!(empty_group.is_subset(&a));
+    }
+}
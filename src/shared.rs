@@ -0,0 +1,129 @@
+//! Copy-on-write sharing of an instance, see [`SortedGroups::shared`].
+use std::{fmt, ops::Deref, sync::Arc};
+
+use crate::{GroupStorage, SortedGroups};
+
+/// [`SortedGroups`] behind an `Arc`, cloned in `O(1)` and deep-copied on the first mutation of a
+/// shared instance.
+///
+/// Reads go through `Deref`, mutations through [`make_mut`](Self::make_mut).
+pub struct SharedSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    inner: Arc<SortedGroups<G, E, S>>,
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Moves `self` behind an `Arc`, so that clones share groups and elements until mutated, e.g.
+    /// to hand snapshots to a render thread while mutating the original.
+    pub fn shared(self) -> SharedSortedGroups<G, E, S> {
+        SharedSortedGroups {
+            inner: Arc::new(self),
+        }
+    }
+}
+
+impl<G, E, S> SharedSortedGroups<G, E, S>
+where
+    G: Ord + Clone,
+    S: GroupStorage<E> + Clone,
+{
+    /// Returns a mutable reference to the groups, deep-copying them first if they are shared with
+    /// another clone.
+    ///
+    /// A copy doesn't keep the observer, as with [`Clone`].
+    pub fn make_mut(&mut self) -> &mut SortedGroups<G, E, S> {
+        Arc::make_mut(&mut self.inner)
+    }
+
+    /// Returns the groups, deep-copying them if they are shared with another clone.
+    pub fn into_inner(self) -> SortedGroups<G, E, S> {
+        Arc::unwrap_or_clone(self.inner)
+    }
+}
+
+impl<G, E, S> SharedSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns whether `self` and `other` share the same groups.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<G, E, S> Clone for SharedSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<G, E, S> Deref for SharedSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    type Target = SortedGroups<G, E, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<G, E, S> From<SortedGroups<G, E, S>> for SharedSortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    fn from(sorted_groups: SortedGroups<G, E, S>) -> Self {
+        sorted_groups.shared()
+    }
+}
+
+impl<G, E, S> fmt::Debug for SharedSortedGroups<G, E, S>
+where
+    G: Ord + fmt::Debug,
+    E: fmt::Debug,
+    S: GroupStorage<E> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_shared_copies_on_write() {
+        let mut shared = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10).shared();
+        let snapshot = shared.clone();
+        assert!(shared.ptr_eq(&snapshot));
+
+        shared.make_mut().insert(13);
+        assert!(!shared.ptr_eq(&snapshot));
+        assert_eq!(shared.len(), 4);
+        assert_eq!(snapshot.len(), 3);
+
+        // A unique instance is mutated in place
+        let before = shared.clone();
+        drop(before);
+        shared.make_mut().insert(22);
+        assert_eq!(shared.into_inner().len(), 5);
+        assert_eq!(snapshot.into_inner().group_len(&1), 2);
+    }
+}
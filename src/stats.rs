@@ -0,0 +1,121 @@
+//! Aggregations over all elements or the elements of a group.
+use std::borrow::Borrow;
+
+use crate::{GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns the number of elements for which `pred` returns `true`.
+    pub fn count_if(&self, mut pred: impl FnMut(&G, &E) -> bool) -> usize {
+        self.iter()
+            .filter(|(group, element)| pred(group, element))
+            .count()
+    }
+
+    /// Returns whether `pred` returns `true` for any element, stopping at the first one.
+    pub fn any(&self, mut pred: impl FnMut(&G, &E) -> bool) -> bool {
+        self.iter().any(|(group, element)| pred(group, element))
+    }
+
+    /// Returns whether `pred` returns `true` for all elements, stopping at the first one for which
+    /// it doesn't.
+    pub fn all(&self, mut pred: impl FnMut(&G, &E) -> bool) -> bool {
+        self.iter().all(|(group, element)| pred(group, element))
+    }
+
+    /// Returns the element with the minimum key `f`, along with its group, the first one if
+    /// several elements have that key.
+    pub fn min_by_key<K: Ord>(&self, mut f: impl FnMut(&G, &E) -> K) -> Option<(&G, &E)> {
+        self.iter().min_by_key(|(group, element)| f(group, element))
+    }
+
+    /// Returns the element with the maximum key `f`, along with its group, the last one if
+    /// several elements have that key.
+    pub fn max_by_key<K: Ord>(&self, mut f: impl FnMut(&G, &E) -> K) -> Option<(&G, &E)> {
+        self.iter().max_by_key(|(group, element)| f(group, element))
+    }
+
+    /// Returns the number of elements of `group` for which `pred` returns `true`.
+    pub fn count_if_in_group<Q>(&self, group: &Q, mut pred: impl FnMut(&E) -> bool) -> usize
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.iter_group(group)
+            .filter(|element| pred(element))
+            .count()
+    }
+
+    /// Returns whether `pred` returns `true` for any element of `group`, stopping at the first
+    /// one.
+    pub fn any_in_group<Q>(&self, group: &Q, pred: impl FnMut(&E) -> bool) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.iter_group(group).any(pred)
+    }
+
+    /// Returns whether `pred` returns `true` for all elements of `group`, stopping at the first
+    /// one for which it doesn't. This is `true` if the group has no elements.
+    pub fn all_in_group<Q>(&self, group: &Q, pred: impl FnMut(&E) -> bool) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.iter_group(group).all(pred)
+    }
+
+    /// Returns the element of `group` with the minimum key `f`, see
+    /// [`min_by_key`](Self::min_by_key).
+    pub fn min_by_key_in_group<Q, K: Ord>(
+        &self,
+        group: &Q,
+        mut f: impl FnMut(&E) -> K,
+    ) -> Option<&E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.iter_group(group).min_by_key(|element| f(element))
+    }
+
+    /// Returns the element of `group` with the maximum key `f`, see
+    /// [`max_by_key`](Self::max_by_key).
+    pub fn max_by_key_in_group<Q, K: Ord>(
+        &self,
+        group: &Q,
+        mut f: impl FnMut(&E) -> K,
+    ) -> Option<&E>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.iter_group(group).max_by_key(|element| f(element))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_aggregations() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 13, 25, 31], |e| e / 10);
+
+        assert_eq!(sorted_groups.count_if(|_, e| e % 2 == 1), 4);
+        assert!(sorted_groups.any(|group, _| *group == 3));
+        assert!(!sorted_groups.all(|_, e| e % 2 == 1));
+        assert_eq!(sorted_groups.min_by_key(|_, e| e % 10), Some((&1, &11)));
+        assert_eq!(sorted_groups.max_by_key(|_, e| e % 10), Some((&2, &25)));
+
+        assert_eq!(sorted_groups.count_if_in_group(&1, |e| *e > 11), 2);
+        assert!(sorted_groups.any_in_group(&1, |e| *e == 12));
+        assert!(sorted_groups.all_in_group(&4, |_| false));
+        assert_eq!(sorted_groups.min_by_key_in_group(&1, |e| -*e), Some(&13));
+        assert_eq!(sorted_groups.max_by_key_in_group(&5, |e| *e), None);
+    }
+}
@@ -0,0 +1,557 @@
+//! Containers holding the elements of each group.
+use std::{
+    borrow::Borrow,
+    collections::{btree_set, BTreeSet},
+    ops::Bound,
+};
+
+/// Container holding the elements of a group, in iteration order.
+///
+/// Implementations are provided for `BTreeSet<E>`, the default, [`SortedVec<E>`] and `Vec<E>`,
+/// which keeps elements in insertion order. The iteration order of the storage defines the flat
+/// order of elements within their group.
+pub trait GroupStorage<E>: Default {
+    type Iter<'a>: DoubleEndedIterator<Item = &'a E>
+    where
+        Self: 'a,
+        E: 'a;
+    type IntoIter: DoubleEndedIterator<Item = E> + ExactSizeIterator;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `element`, returning its position in iteration order if it was inserted.
+    fn insert_at(&mut self, element: E) -> Option<usize>;
+
+    /// Inserts `element`, returning whether it was inserted.
+    ///
+    /// The default implementation calls [`insert_at`](Self::insert_at), storages that need extra
+    /// work to compute the position of the element should override it.
+    fn insert(&mut self, element: E) -> bool {
+        self.insert_at(element).is_some()
+    }
+
+    /// Appends `element` after all other elements, giving it back if that would break the
+    /// ordering of the storage.
+    ///
+    /// Elements pushed in the iteration order of another instance of the storage must be accepted.
+    fn push(&mut self, element: E) -> Result<(), E>;
+
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Returns an iterator starting at the `n`th element, which is empty if `n` is out of bounds.
+    ///
+    /// This must yield the same elements as `iter().skip(n)`.
+    fn iter_from(&self, n: usize) -> Self::Iter<'_>;
+
+    fn into_elements(self) -> Self::IntoIter;
+
+    /// Returns the `n`th element in iteration order.
+    fn nth(&self, n: usize) -> Option<&E>;
+
+    fn pop_first(&mut self) -> Option<E>;
+
+    fn pop_last(&mut self) -> Option<E>;
+
+    /// Removes and returns the `n`th element in iteration order.
+    ///
+    /// The default implementation splits off the elements from the `n`th one and pushes back
+    /// those after it.
+    fn remove_nth(&mut self, n: usize) -> Option<E> {
+        let mut tail = self.split_off(n);
+        let element = tail.pop_first();
+        for element in tail.into_elements() {
+            // Elements come in iteration order after the remaining ones, which is accepted
+            let _ = self.push(element);
+        }
+        element
+    }
+
+    /// Retains only the elements for which `f` returns `true`, calling it on every element in
+    /// iteration order.
+    fn retain(&mut self, f: impl FnMut(&E) -> bool);
+
+    /// Splits off the elements from the `n`th one, returning them.
+    ///
+    /// The default implementation moves all elements to either storage with
+    /// [`push`](Self::push).
+    fn split_off(&mut self, n: usize) -> Self {
+        let mut tail = Self::default();
+        for (index, element) in std::mem::take(self).into_elements().enumerate() {
+            let target = if index < n { &mut *self } else { &mut tail };
+            // Elements come in iteration order, which both targets accept
+            let _ = target.push(element);
+        }
+        tail
+    }
+
+    /// Removes and returns the elements for which `pred` returns `true`, calling it on every element
+    /// in iteration order.
+    ///
+    /// The default implementation moves all elements to either storage with
+    /// [`push`](Self::push).
+    fn extract_if(&mut self, mut pred: impl FnMut(&E) -> bool) -> Self {
+        let mut extracted = Self::default();
+        for element in std::mem::take(self).into_elements() {
+            let target = if pred(&element) {
+                &mut extracted
+            } else {
+                &mut *self
+            };
+            // Elements come in iteration order, which both targets accept
+            let _ = target.push(element);
+        }
+        extracted
+    }
+}
+
+/// Lookup of elements by a borrowed form `Q` in a [`GroupStorage`].
+pub trait GroupLookup<E, Q: ?Sized>: GroupStorage<E> {
+    fn contains(&self, element: &Q) -> bool;
+
+    /// Removes and returns the first element equal to `element`.
+    fn take(&mut self, element: &Q) -> Option<E>;
+
+    /// Returns the position of the first element equal to `element`.
+    fn rank(&self, element: &Q) -> Option<usize>;
+
+    /// Returns the number of elements equal to `element`.
+    fn count(&self, element: &Q) -> usize;
+
+    /// Returns the position `element` has or would have once inserted, i.e. the position of the
+    /// first element equal to it or, if there is none, of the first one ordered after it.
+    ///
+    /// Storages without an ordering, like `Vec<E>`, return their length for absent elements.
+    fn lower_bound(&self, element: &Q) -> usize;
+}
+
+impl<E> GroupStorage<E> for BTreeSet<E>
+where
+    E: Ord,
+{
+    type Iter<'a>
+        = btree_set::Range<'a, E>
+    where
+        E: 'a;
+    type IntoIter = btree_set::IntoIter<E>;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn insert_at(&mut self, element: E) -> Option<usize> {
+        if self.contains(&element) {
+            return None;
+        }
+        let rank = self.range(..&element).count();
+        self.insert(element);
+        Some(rank)
+    }
+
+    fn insert(&mut self, element: E) -> bool {
+        self.insert(element)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        if self.last().is_some_and(|last| last >= &element) {
+            return Err(element);
+        }
+        self.insert(element);
+        Ok(())
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.range(..)
+    }
+
+    fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+        match (GroupStorage::nth(self, n), self.last()) {
+            (Some(first), _) => self.range(first..),
+            (None, Some(last)) => self.range((Bound::Excluded(last), Bound::Unbounded)),
+            (None, None) => self.range(..),
+        }
+    }
+
+    fn into_elements(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+
+    // `BTreeSet` has no positional access, walk it from the closest end
+    fn nth(&self, n: usize) -> Option<&E> {
+        if n < self.len() / 2 {
+            self.iter().nth(n)
+        } else {
+            self.iter().nth_back(self.len().checked_sub(n + 1)?)
+        }
+    }
+
+    fn pop_first(&mut self) -> Option<E> {
+        self.pop_first()
+    }
+
+    fn pop_last(&mut self) -> Option<E> {
+        self.pop_last()
+    }
+
+    fn retain(&mut self, f: impl FnMut(&E) -> bool) {
+        self.retain(f);
+    }
+}
+
+impl<E, Q> GroupLookup<E, Q> for BTreeSet<E>
+where
+    E: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    fn contains(&self, element: &Q) -> bool {
+        self.contains(element)
+    }
+
+    fn take(&mut self, element: &Q) -> Option<E> {
+        self.take(element)
+    }
+
+    fn rank(&self, element: &Q) -> Option<usize> {
+        if !self.contains(element) {
+            return None;
+        }
+        Some(
+            self.range::<Q, _>((Bound::Unbounded, Bound::Excluded(element)))
+                .count(),
+        )
+    }
+
+    fn count(&self, element: &Q) -> usize {
+        usize::from(self.contains(element))
+    }
+
+    fn lower_bound(&self, element: &Q) -> usize {
+        // `BTreeSet` has no order statistics, so this is linear in the elements before `element`
+        self.range::<Q, _>((Bound::Unbounded, Bound::Excluded(element)))
+            .count()
+    }
+}
+
+/// Sorted vector allowing duplicate elements, equal elements being kept in insertion order.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SortedVec<E>(Vec<E>);
+
+impl<E> SortedVec<E> {
+    pub fn as_slice(&self) -> &[E] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<E> {
+        self.0
+    }
+}
+
+impl<E> Default for SortedVec<E> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<'a, E> IntoIterator for &'a SortedVec<E> {
+    type Item = &'a E;
+    type IntoIter = std::slice::Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<E> GroupStorage<E> for SortedVec<E>
+where
+    E: Ord,
+{
+    type Iter<'a>
+        = std::slice::Iter<'a, E>
+    where
+        E: 'a;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn insert_at(&mut self, element: E) -> Option<usize> {
+        let index = self.0.partition_point(|e| e <= &element);
+        self.0.insert(index, element);
+        Some(index)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        if self.0.last().is_some_and(|last| last > &element) {
+            return Err(element);
+        }
+        self.0.push(element);
+        Ok(())
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.0.iter()
+    }
+
+    fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+        self.0[n.min(self.0.len())..].iter()
+    }
+
+    fn into_elements(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+
+    fn nth(&self, n: usize) -> Option<&E> {
+        self.0.get(n)
+    }
+
+    fn pop_first(&mut self) -> Option<E> {
+        if self.0.is_empty() {
+            return None;
+        }
+        Some(self.0.remove(0))
+    }
+
+    fn pop_last(&mut self) -> Option<E> {
+        self.0.pop()
+    }
+
+    fn remove_nth(&mut self, n: usize) -> Option<E> {
+        (n < self.0.len()).then(|| self.0.remove(n))
+    }
+
+    fn retain(&mut self, f: impl FnMut(&E) -> bool) {
+        self.0.retain(f);
+    }
+
+    fn split_off(&mut self, n: usize) -> Self {
+        Self(self.0.split_off(n.min(self.0.len())))
+    }
+}
+
+impl<E, Q> GroupLookup<E, Q> for SortedVec<E>
+where
+    E: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    fn contains(&self, element: &Q) -> bool {
+        self.rank(element).is_some()
+    }
+
+    fn take(&mut self, element: &Q) -> Option<E> {
+        let index = self.rank(element)?;
+        Some(self.0.remove(index))
+    }
+
+    fn rank(&self, element: &Q) -> Option<usize> {
+        let index = self.0.partition_point(|e| e.borrow() < element);
+        self.0
+            .get(index)
+            .is_some_and(|e| e.borrow() == element)
+            .then_some(index)
+    }
+
+    fn count(&self, element: &Q) -> usize {
+        let start = self.0.partition_point(|e| e.borrow() < element);
+        let end = self.0.partition_point(|e| e.borrow() <= element);
+        end - start
+    }
+
+    fn lower_bound(&self, element: &Q) -> usize {
+        self.0.partition_point(|e| e.borrow() < element)
+    }
+}
+
+/// Elements are kept in insertion order, duplicates included.
+impl<E> GroupStorage<E> for Vec<E> {
+    type Iter<'a>
+        = std::slice::Iter<'a, E>
+    where
+        E: 'a;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn insert_at(&mut self, element: E) -> Option<usize> {
+        self.push(element);
+        Some(self.len() - 1)
+    }
+
+    fn push(&mut self, element: E) -> Result<(), E> {
+        self.push(element);
+        Ok(())
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.as_slice().iter()
+    }
+
+    fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+        self[n.min(self.len())..].iter()
+    }
+
+    fn into_elements(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+
+    fn nth(&self, n: usize) -> Option<&E> {
+        self.get(n)
+    }
+
+    fn pop_first(&mut self) -> Option<E> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.remove(0))
+    }
+
+    fn pop_last(&mut self) -> Option<E> {
+        self.pop()
+    }
+
+    fn remove_nth(&mut self, n: usize) -> Option<E> {
+        (n < self.len()).then(|| self.remove(n))
+    }
+
+    fn retain(&mut self, f: impl FnMut(&E) -> bool) {
+        self.retain(f);
+    }
+
+    fn split_off(&mut self, n: usize) -> Self {
+        self.split_off(n.min(self.len()))
+    }
+}
+
+impl<E, Q> GroupLookup<E, Q> for Vec<E>
+where
+    E: Borrow<Q>,
+    Q: PartialEq + ?Sized,
+{
+    fn contains(&self, element: &Q) -> bool {
+        self.rank(element).is_some()
+    }
+
+    fn take(&mut self, element: &Q) -> Option<E> {
+        let index = self.rank(element)?;
+        Some(self.remove(index))
+    }
+
+    fn rank(&self, element: &Q) -> Option<usize> {
+        self.as_slice().iter().position(|e| e.borrow() == element)
+    }
+
+    fn count(&self, element: &Q) -> usize {
+        self.as_slice()
+            .iter()
+            .filter(|e| (*e).borrow() == element)
+            .count()
+    }
+
+    fn lower_bound(&self, element: &Q) -> usize {
+        self.rank(element).unwrap_or(self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_vec_keeps_duplicates() {
+        let mut elements = SortedVec::default();
+        for element in [2, 1, 2, 1, 2] {
+            assert!(GroupStorage::insert(&mut elements, element));
+        }
+        assert_eq!(elements.as_slice(), &[1, 1, 2, 2, 2]);
+        assert_eq!(elements.count(&2), 3);
+        assert_eq!(elements.rank(&2), Some(2));
+        assert_eq!(elements.take(&1), Some(1));
+        assert_eq!(elements.as_slice(), &[1, 2, 2, 2]);
+    }
+
+    #[test]
+    fn test_btree_set_iter_from() {
+        let elements = BTreeSet::from([1, 2, 3]);
+        assert!(elements.iter_from(1).eq(&[2, 3]));
+        assert_eq!(elements.iter_from(3).next(), None);
+        assert_eq!(BTreeSet::<i32>::new().iter_from(0).next(), None);
+    }
+
+    // Keeps the most recent element first
+    #[derive(Default)]
+    struct NewestFirst(std::collections::VecDeque<i32>);
+
+    impl GroupStorage<i32> for NewestFirst {
+        type Iter<'a> = std::collections::vec_deque::Iter<'a, i32>;
+        type IntoIter = std::collections::vec_deque::IntoIter<i32>;
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn insert_at(&mut self, element: i32) -> Option<usize> {
+            self.0.push_front(element);
+            Some(0)
+        }
+
+        fn push(&mut self, element: i32) -> Result<(), i32> {
+            self.0.push_back(element);
+            Ok(())
+        }
+
+        fn iter(&self) -> Self::Iter<'_> {
+            self.0.iter()
+        }
+
+        fn iter_from(&self, n: usize) -> Self::Iter<'_> {
+            self.0.range(n.min(self.0.len())..)
+        }
+
+        fn into_elements(self) -> Self::IntoIter {
+            self.0.into_iter()
+        }
+
+        fn nth(&self, n: usize) -> Option<&i32> {
+            self.0.get(n)
+        }
+
+        fn pop_first(&mut self) -> Option<i32> {
+            self.0.pop_front()
+        }
+
+        fn pop_last(&mut self) -> Option<i32> {
+            self.0.pop_back()
+        }
+
+        fn retain(&mut self, f: impl FnMut(&i32) -> bool) {
+            self.0.retain(f);
+        }
+    }
+
+    #[test]
+    fn test_custom_storage() {
+        let mut sorted_groups =
+            crate::SortedGroups::<i32, i32, NewestFirst>::new_with_storage(vec![11, 21, 12], |e| {
+                e / 10
+            });
+        sorted_groups.insert(13);
+
+        assert_eq!(
+            sorted_groups.iter().collect::<Vec<_>>(),
+            vec![(&1, &13), (&1, &12), (&1, &11), (&2, &21)]
+        );
+        assert_eq!(sorted_groups.get(1), Some((&1, &12)));
+        assert_eq!(
+            sorted_groups
+                .extract_if(|_, e| e % 2 == 1)
+                .collect::<Vec<_>>(),
+            vec![(1, 13), (1, 11), (2, 21)]
+        );
+        assert_eq!(sorted_groups.iter().collect::<Vec<_>>(), vec![(&1, &12)]);
+    }
+}
@@ -0,0 +1,75 @@
+//! Stream of changes, enabled by the `futures` feature.
+use futures_channel::mpsc;
+use futures_core::Stream;
+
+use crate::{Change, GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord + Clone + Send + 'static,
+    E: Clone + Send + 'static,
+    S: GroupStorage<E>,
+{
+    /// Returns a stream of the changes made from now on, as notified to an observer, see
+    /// [`set_observer`](Self::set_observer).
+    ///
+    /// Changes are buffered until the stream is polled, which ends once the `SortedGroups` or its
+    /// observer is dropped. Any number of streams can be subscribed, along with the observer
+    /// already set. Setting another observer afterwards ends them.
+    pub fn subscribe(&mut self) -> impl Stream<Item = Change<G, E>> + Send + Unpin {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut previous = self.observer.take();
+        // Batches are passed on as such to the previous observer
+        self.set_batch_observer(move |changes| {
+            if let Some(previous) = &mut previous {
+                previous.notify_batch(changes);
+            }
+            for change in changes {
+                // The stream may have been dropped, which only stops its own notifications
+                let _ = sender.unbounded_send(change.cloned());
+            }
+        });
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, StreamExt};
+
+    use crate::{Change, SortedGroups};
+
+    #[test]
+    fn test_subscribe() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        let first = sorted_groups.subscribe();
+        sorted_groups.insert(12);
+        let second = sorted_groups.subscribe();
+        sorted_groups.remove_group(&1);
+        drop(sorted_groups);
+
+        let inserted = Change::ElementInserted {
+            group: 1,
+            element: 12,
+            index: 1,
+        };
+        let removed = vec![
+            Change::ElementRemoved {
+                group: 1,
+                element: 12,
+                index: 1,
+            },
+            Change::ElementRemoved {
+                group: 1,
+                element: 11,
+                index: 0,
+            },
+            Change::GroupRemoved { group: 1, index: 0 },
+        ];
+        assert_eq!(
+            block_on(first.collect::<Vec<_>>()),
+            [vec![inserted], removed.clone()].concat()
+        );
+        assert_eq!(block_on(second.collect::<Vec<_>>()), removed);
+    }
+}
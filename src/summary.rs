@@ -0,0 +1,215 @@
+//! Aggregates kept up to date for each group, see [`SortedGroups::set_summary`].
+use std::{any::Any, borrow::Borrow, ops::RangeBounds};
+
+use crate::{GroupStorage, SortedGroups};
+
+/// Aggregate of the elements of a group, such as a count, a sum or bounds, updated incrementally
+/// on every change, see [`SortedGroups::set_summary`].
+///
+/// The default value summarizes a group without elements.
+pub trait GroupSummary<E>: Clone + Default + Send + Sync + 'static {
+    /// Accounts for `element` being added to the group.
+    fn add(&mut self, element: &E);
+
+    /// Accounts for `element` being removed from the group.
+    fn remove(&mut self, element: &E);
+
+    /// Combines the summary of another group into this one.
+    fn merge(&mut self, other: &Self);
+}
+
+// Summary of each group, hiding the summary type from `SortedGroups`
+pub(crate) trait Summaries<E>: Send + Sync {
+    fn insert_group(&mut self, index: usize);
+
+    fn remove_group(&mut self, index: usize);
+
+    // Accounts for `element` being inserted in or removed from the group at `index`
+    fn update(&mut self, index: usize, element: &E, inserted: bool);
+
+    fn clone_box(&self) -> Box<dyn Summaries<E>>;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<E, T> Summaries<E> for Vec<T>
+where
+    T: GroupSummary<E>,
+{
+    fn insert_group(&mut self, index: usize) {
+        self.insert(index, T::default());
+    }
+
+    fn remove_group(&mut self, index: usize) {
+        self.remove(index);
+    }
+
+    fn update(&mut self, index: usize, element: &E, inserted: bool) {
+        if inserted {
+            self[index].add(element);
+        } else {
+            self[index].remove(element);
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Summaries<E>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Keeps a summary `T` of each group, computed from the current elements and then updated on
+    /// every change, replacing the previous summary type if any.
+    ///
+    /// Mutations then go through each changed element, as when an observer is set, see
+    /// [`set_observer`](Self::set_observer). Summaries are cloned along with the `SortedGroups`,
+    /// but not carried over to the parts split off from it.
+    pub fn set_summary<T: GroupSummary<E>>(&mut self) {
+        let summaries = self
+            .groups
+            .iter()
+            .map(|(_, elements)| {
+                let mut summary = T::default();
+                for element in elements.iter() {
+                    summary.add(element);
+                }
+                summary
+            })
+            .collect::<Vec<_>>();
+        self.summaries = Some(Box::new(summaries));
+    }
+
+    /// Stops keeping the summaries set with [`set_summary`](Self::set_summary).
+    pub fn remove_summary(&mut self) {
+        self.summaries = None;
+    }
+
+    /// Returns the summary `T` of `group`, which is `None` if the group doesn't exist or if `T` is
+    /// not the type set with [`set_summary`](Self::set_summary).
+    pub fn summary<T, Q>(&self, group: &Q) -> Option<&T>
+    where
+        T: GroupSummary<E>,
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.group_index(group).ok()?;
+        self.summaries::<T>()?.get(index)
+    }
+
+    /// Returns the summaries `T` of the groups within `range` merged together, see
+    /// [`summary`](Self::summary).
+    pub fn range_summary<T, Q, R>(&self, range: R) -> Option<T>
+    where
+        T: GroupSummary<E>,
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let summaries = self.summaries::<T>()?;
+        let mut merged = T::default();
+        for summary in &summaries[self.group_indices(range)] {
+            merged.merge(summary);
+        }
+        Some(merged)
+    }
+
+    fn summaries<T: GroupSummary<E>>(&self) -> Option<&Vec<T>> {
+        self.summaries.as_ref()?.as_any().downcast_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Sum {
+        count: usize,
+        total: i32,
+    }
+
+    impl GroupSummary<i32> for Sum {
+        fn add(&mut self, element: &i32) {
+            self.count += 1;
+            self.total += element;
+        }
+
+        fn remove(&mut self, element: &i32) {
+            self.count -= 1;
+            self.total -= element;
+        }
+
+        fn merge(&mut self, other: &Self) {
+            self.count += other.count;
+            self.total += other.total;
+        }
+    }
+
+    fn sum(count: usize, total: i32) -> Option<Sum> {
+        Some(Sum { count, total })
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21], |e| e / 10);
+        sorted_groups.set_summary::<Sum>();
+        assert_eq!(sorted_groups.summary(&1).cloned(), sum(2, 23));
+
+        sorted_groups.insert(13);
+        sorted_groups.insert(31);
+        sorted_groups.remove(&1, &11);
+        assert_eq!(sorted_groups.summary(&1).cloned(), sum(2, 25));
+        assert_eq!(sorted_groups.summary(&3).cloned(), sum(1, 31));
+        assert_eq!(sorted_groups.summary::<Sum, _>(&4), None);
+        assert_eq!(sorted_groups.range_summary(2..), sum(2, 52));
+
+        sorted_groups.retain(|_, e| e % 2 == 1);
+        sorted_groups.merge_groups(&2, 1);
+        sorted_groups.append(&mut SortedGroups::new(vec![15, 41], |e| e / 10));
+        assert_eq!(sorted_groups.summary(&1).cloned(), sum(3, 49));
+        assert_eq!(sorted_groups.summary::<Sum, _>(&2), None);
+        assert_eq!(sorted_groups.range_summary(..), sum(5, 121));
+
+        let clone = sorted_groups.clone();
+        sorted_groups.clear();
+        assert_eq!(sorted_groups.range_summary(..), sum(0, 0));
+        assert_eq!(clone.summary(&4).cloned(), sum(1, 41));
+    }
+
+    #[test]
+    fn test_summary_type_mismatch() {
+        #[derive(Clone, Default)]
+        struct Count(usize);
+
+        impl GroupSummary<i32> for Count {
+            fn add(&mut self, _: &i32) {
+                self.0 += 1;
+            }
+
+            fn remove(&mut self, _: &i32) {
+                self.0 -= 1;
+            }
+
+            fn merge(&mut self, other: &Self) {
+                self.0 += other.0;
+            }
+        }
+
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        assert!(sorted_groups.summary::<Count, _>(&1).is_none());
+        sorted_groups.set_summary::<Count>();
+        assert_eq!(
+            sorted_groups.summary::<Count, _>(&1).map(|count| count.0),
+            Some(1)
+        );
+        assert!(sorted_groups.summary::<Sum, _>(&1).is_none());
+    }
+}
@@ -0,0 +1,306 @@
+//! Atomic batches of mutations, see [`SortedGroups::transaction`].
+use std::{borrow::Borrow, collections::BTreeSet, ops::Deref};
+
+use crate::{
+    history::{inverse, History},
+    observer::{notify_batch, Observer},
+    ApplyError, Change, SortedGroups,
+};
+
+/// Mutations made within a [`SortedGroups::transaction`], read access being given through
+/// `Deref`.
+pub struct Tx<'a, G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    sorted_groups: &'a mut SortedGroups<G, E>,
+}
+
+impl<G, E> Tx<'_, G, E>
+where
+    G: Ord + Clone,
+    E: Ord,
+{
+    /// Same as [`SortedGroups::insert`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no grouping function, see [`SortedGroups::from_pairs`].
+    pub fn insert(&mut self, element: E) -> bool {
+        self.sorted_groups.insert(element)
+    }
+
+    /// Same as [`SortedGroups::insert_with_group`].
+    pub fn insert_with_group(&mut self, group: G, element: E) -> bool {
+        self.sorted_groups.insert_with_group(group, element)
+    }
+
+    /// Same as [`SortedGroups::remove`].
+    pub fn remove<Q, R>(&mut self, group: &Q, element: &R) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.sorted_groups.remove(group, element)
+    }
+
+    /// Same as [`SortedGroups::update`].
+    pub fn update<Q, R>(&mut self, group: &Q, element: &R, f: impl FnOnce(E) -> E) -> bool
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+        E: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.sorted_groups.update(group, element, f)
+    }
+
+    /// Same as [`SortedGroups::add_group`].
+    pub fn add_group(&mut self, group: G) -> bool {
+        self.sorted_groups.add_group(group)
+    }
+
+    /// Same as [`SortedGroups::clear_group`].
+    pub fn clear_group<Q>(&mut self, group: &Q)
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.sorted_groups.clear_group(group);
+    }
+
+    /// Same as [`SortedGroups::remove_group`].
+    pub fn remove_group<Q>(&mut self, group: &Q) -> Option<BTreeSet<E>>
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.sorted_groups.remove_group(group)
+    }
+}
+
+impl<G, E> Deref for Tx<'_, G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    type Target = SortedGroups<G, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.sorted_groups
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    /// Calls `f` to make mutations that are kept if it returns `Ok` and rolled back if it returns
+    /// `Err` or panics.
+    ///
+    /// The observer and history are notified of the changes once `f` returns `Ok`, and not at all
+    /// if the changes are rolled back. An observer set with
+    /// [`set_batch_observer`](Self::set_batch_observer) receives them as a single batch, and a
+    /// single [`undo`](Self::undo) reverts them all.
+    pub fn transaction<Err>(
+        &mut self,
+        f: impl FnOnce(&mut Tx<'_, G, E>) -> Result<(), Err>,
+    ) -> Result<(), Err> {
+        let mut guard = TxGuard {
+            observer: self.observer.take(),
+            history: self.history.take(),
+            sorted_groups: self,
+        };
+        // Changes are recorded by a history of their own, to be rolled back or notified later
+        guard.sorted_groups.enable_history();
+
+        let result = f(&mut Tx {
+            sorted_groups: guard.sorted_groups,
+        });
+        let changes = guard.take_changes();
+        match result {
+            Ok(()) => {
+                let changes = changes.iter().map(Change::as_ref).collect::<Vec<_>>();
+                notify_batch(&mut guard.observer, &mut guard.history, &changes);
+            }
+            Err(_) => guard
+                .roll_back(changes)
+                .expect("recorded changes match the current state"),
+        }
+        result
+    }
+}
+
+// Puts the observer and history back once a transaction ends, rolling back its changes if it
+// didn't return
+struct TxGuard<'a, G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    sorted_groups: &'a mut SortedGroups<G, E>,
+    observer: Option<Observer<G, E>>,
+    history: Option<History<G, E>>,
+}
+
+impl<G, E> TxGuard<'_, G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    // Takes the changes recorded by the transaction, stopping their recording
+    fn take_changes(&mut self) -> Vec<Change<G, E>> {
+        self.sorted_groups
+            .history
+            .take()
+            .map(|mut history| history.take_recorded())
+            .unwrap_or_default()
+    }
+
+    fn roll_back(&mut self, changes: Vec<Change<G, E>>) -> Result<(), ApplyError> {
+        self.sorted_groups.apply(changes.iter().rev().map(inverse))
+    }
+}
+
+impl<G, E> Drop for TxGuard<'_, G, E>
+where
+    G: Ord + Clone,
+    E: Ord + Clone,
+{
+    fn drop(&mut self) {
+        // Changes are still being recorded only if `f` panicked. A mutation interrupted by a
+        // panic, e.g. in `Ord`, may not match its recorded change, in which case the rollback
+        // stops there rather than panicking while unwinding
+        if self.sorted_groups.history.is_some() {
+            let changes = self.take_changes();
+            let _ = self.roll_back(changes);
+        }
+        self.sorted_groups.observer = self.observer.take();
+        self.sorted_groups.history = self.history.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{Change, SortedGroups};
+
+    #[test]
+    fn test_transaction() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21], |e| e / 10);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        sorted_groups.set_observer({
+            let changes = changes.clone();
+            move |change| changes.lock().unwrap().push(change.cloned())
+        });
+        let before = sorted_groups.clone();
+
+        let result = sorted_groups.transaction(|tx| {
+            tx.insert(12);
+            tx.remove_group(&2);
+            tx.update(&1, &11, |e| e + 20);
+            assert_eq!(tx.len(), 2);
+            if tx.contains(&3, &31) {
+                return Err("conflict");
+            }
+            Ok(())
+        });
+        assert_eq!(result, Err("conflict"));
+        assert_eq!(sorted_groups, before);
+        assert!(changes.lock().unwrap().is_empty());
+
+        let result = sorted_groups.transaction(|tx| {
+            tx.insert(12);
+            tx.insert(41);
+            Ok::<_, ()>(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(sorted_groups.len(), 4);
+        let changes = std::mem::take(&mut *changes.lock().unwrap());
+        // The changes are those of the committed transaction only
+        let mut replica = before.clone();
+        replica.apply(changes).unwrap();
+        assert_eq!(replica, sorted_groups);
+    }
+
+    #[test]
+    fn test_transaction_notifies_one_batch() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        sorted_groups.set_batch_observer({
+            let batches = batches.clone();
+            move |changes| {
+                let changes = changes.iter().map(Change::cloned).collect::<Vec<_>>();
+                batches.lock().unwrap().push(changes);
+            }
+        });
+
+        sorted_groups.insert(13);
+        let result = sorted_groups.transaction(|tx| {
+            tx.insert(12);
+            tx.insert(21);
+            Ok::<_, ()>(())
+        });
+        assert_eq!(result, Ok(()));
+        let batches = batches.lock().unwrap();
+        assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_undo_reverts_whole_transaction() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        sorted_groups.enable_history();
+        sorted_groups.insert(12);
+        let before = sorted_groups.clone();
+
+        let result = sorted_groups.transaction(|tx| {
+            tx.insert(21);
+            tx.insert(22);
+            assert_eq!(tx.remove_group(&1).map(|elements| elements.len()), Some(2));
+            Ok::<_, ()>(())
+        });
+        assert_eq!(result, Ok(()));
+        assert!(sorted_groups.undo());
+        assert_eq!(sorted_groups, before);
+        assert!(sorted_groups.redo());
+        assert_eq!(sorted_groups.iter().count(), 2);
+        assert!(sorted_groups.undo());
+        assert!(sorted_groups.undo());
+        assert_eq!(sorted_groups.len(), 1);
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_panic() {
+        let mut sorted_groups = SortedGroups::<i32, i32>::new(vec![11], |e| e / 10);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        sorted_groups.set_observer({
+            let changes = changes.clone();
+            move |change| changes.lock().unwrap().push(change.cloned())
+        });
+        sorted_groups.enable_history();
+        let before = sorted_groups.clone();
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            sorted_groups.transaction::<()>(|tx| {
+                tx.insert(21);
+                panic!("aborted");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(sorted_groups, before);
+
+        // The observer and history are back in place
+        sorted_groups.insert(12);
+        assert_eq!(changes.lock().unwrap().len(), 1);
+        assert!(sorted_groups.undo());
+        assert_eq!(sorted_groups, before);
+    }
+}
@@ -0,0 +1,208 @@
+//! Consuming transformations into a new instance.
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use crate::{GroupFn, GroupStorage, SortedGroups};
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Regroups all elements by `group_from_element`, which replaces the grouping function.
+    ///
+    /// Previous groups are dropped, including empty ones, the settings of
+    /// [`keep_empty_groups`](Self::keep_empty_groups) and
+    /// [`descending_groups`](Self::descending_groups) are kept.
+    pub fn regroup_by<G2: Ord>(
+        self,
+        group_from_element: impl Fn(&E) -> G2 + Send + Sync + 'static,
+    ) -> SortedGroups<G2, E, S> {
+        let keep_empty_groups = self.keep_empty_groups;
+        let descending_groups = self.descending_groups;
+        let elements = self
+            .groups
+            .into_iter()
+            .flat_map(|(_, elements)| elements.into_elements());
+        let mut regrouped = SortedGroups::new_with_storage(elements, group_from_element);
+        regrouped.keep_empty_groups = keep_empty_groups;
+        regrouped.sort_groups(descending_groups);
+        regrouped
+    }
+
+    /// Reverses the order of groups, switching between ascending and descending groups, each
+    /// group keeping the order of its elements.
+    ///
+    /// Every positional API follows the order of groups, such as flat indices with
+    /// [`get`](Self::get), [`first`](Self::first), iteration, cursors and pages. As with other
+    /// transformations, the observer, history, summaries and indexes are not carried over.
+    pub fn reverse_groups(self) -> Self {
+        let descending_groups = self.descending_groups;
+        let mut reversed = Self::from_parts(self.groups, self.group_from_element);
+        reversed.keep_empty_groups = self.keep_empty_groups;
+        reversed.descending_groups = descending_groups;
+        reversed.sort_groups(!descending_groups);
+        reversed
+    }
+
+    /// Maps every group with `f`, merging the elements of groups mapped to the same group.
+    ///
+    /// The grouping function, if any, is then followed by `f`.
+    pub fn map_groups<G2: Ord>(
+        self,
+        f: impl Fn(G) -> G2 + Send + Sync + 'static,
+    ) -> SortedGroups<G2, E, S>
+    where
+        G: 'static,
+        E: 'static,
+    {
+        let f = Arc::new(f);
+        let mut groups = BTreeMap::<G2, S>::new();
+        for (group, elements) in self.groups {
+            match groups.entry(f(group)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(elements);
+                }
+                Entry::Occupied(mut entry) => {
+                    for element in elements.into_elements() {
+                        entry.get_mut().insert(element);
+                    }
+                }
+            }
+        }
+        let group_from_element = self.group_from_element.map(|group_from_element| {
+            Arc::new(move |element: &E| f(group_from_element(element))) as GroupFn<E, G2>
+        });
+        let mut mapped = SortedGroups::from_parts(groups.into_iter().collect(), group_from_element);
+        mapped.keep_empty_groups = self.keep_empty_groups;
+        mapped.sort_groups(self.descending_groups);
+        mapped
+    }
+}
+
+impl<G, E> SortedGroups<G, E>
+where
+    G: Ord,
+    E: Ord,
+{
+    /// Maps every element with `f`, keeping it in its group, elements mapped to the same element
+    /// being merged.
+    ///
+    /// As the grouping function applies to the previous elements, it is not kept, so elements
+    /// must be inserted with [`insert_with_group`](SortedGroups::insert_with_group) afterwards.
+    pub fn map_elements<E2: Ord>(self, f: impl Fn(E) -> E2) -> SortedGroups<G, E2> {
+        let groups = self
+            .groups
+            .into_iter()
+            .map(|(group, elements)| (group, elements.into_iter().map(&f).collect::<BTreeSet<_>>()))
+            .collect();
+        let mut mapped = SortedGroups::from_parts(groups, None);
+        mapped.keep_empty_groups = self.keep_empty_groups;
+        mapped.descending_groups = self.descending_groups;
+        mapped
+    }
+
+    /// Maps every group and element with `f`, dropping those for which it returns `None`, in a
+    /// single pass.
+    ///
+    /// Like [`from_pairs`](SortedGroups::from_pairs), the result has no grouping function and only
+    /// groups with elements. The setting of [`descending_groups`](Self::descending_groups) is
+    /// kept.
+    pub fn filter_map<G2, E2>(self, f: impl Fn(G, E) -> Option<(G2, E2)>) -> SortedGroups<G2, E2>
+    where
+        G: Clone,
+        G2: Ord,
+        E2: Ord,
+    {
+        let mut mapped = SortedGroups::from_parts(Vec::new(), None);
+        mapped.descending_groups = self.descending_groups;
+        // Groups mapped in order are appended one at a time, others are inserted where they
+        // belong
+        for (group, elements) in self.groups {
+            for element in elements {
+                if let Some((group, element)) = f(group.clone(), element) {
+                    mapped.push_or_insert(group, element);
+                }
+            }
+        }
+        mapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_regroup_by() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 22], |e| e / 10);
+
+        let mut regrouped = sorted_groups.regroup_by(|e| e % 2 == 0);
+        assert_eq!(
+            regrouped.iter().collect::<Vec<_>>(),
+            vec![(&false, &11), (&false, &21), (&true, &12), (&true, &22)]
+        );
+        regrouped.insert(31);
+        assert_eq!(regrouped.group_len(&false), 3);
+    }
+
+    #[test]
+    fn test_map_groups_and_elements() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31], |e| e / 10);
+
+        let mut mapped = sorted_groups.map_groups(|group| group % 2);
+        assert_eq!(
+            mapped.iter().collect::<Vec<_>>(),
+            vec![(&0, &21), (&0, &22), (&1, &11), (&1, &31)]
+        );
+        mapped.insert(41);
+        assert_eq!(mapped.group_len(&0), 3);
+
+        let mapped = mapped.map_elements(|e| e % 10);
+        assert_eq!(
+            mapped.iter().collect::<Vec<_>>(),
+            vec![(&0, &1), (&0, &2), (&1, &1)]
+        );
+        assert_eq!(mapped.len(), 3);
+    }
+
+    #[test]
+    fn test_transforms_keep_descending_groups() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 21, 22, 31], |e| e / 10).reverse_groups();
+
+        let mapped = sorted_groups.clone().map_groups(|group| group % 2);
+        assert_eq!(
+            mapped.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &31), (&0, &21), (&0, &22)]
+        );
+        let regrouped = sorted_groups.clone().regroup_by(|e| e % 10);
+        assert_eq!(regrouped.first(), Some((&2, &22)));
+        let filtered = sorted_groups.filter_map(|group, e| Some((group, e)));
+        assert_eq!(filtered.first(), Some((&3, &31)));
+        assert!(filtered.descending_groups());
+    }
+
+    #[test]
+    fn test_filter_map() {
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 32], |e| e / 10);
+
+        let filtered = sorted_groups
+            .filter_map(|group, e| (e % 2 == 0).then(|| (format!("g{group}"), e * 10)));
+        assert_eq!(
+            filtered.iter().collect::<Vec<_>>(),
+            vec![(&"g1".to_string(), &120), (&"g3".to_string(), &320)]
+        );
+        assert_eq!(filtered.groups_len(), 2);
+
+        let sorted_groups = SortedGroups::<i32, i32>::new(vec![11, 12, 21, 32], |e| e / 10);
+        let reversed = sorted_groups.filter_map(|group, e| Some((-(group % 3), -e)));
+        assert_eq!(
+            reversed.iter().collect::<Vec<_>>(),
+            vec![(&-2, &-21), (&-1, &-12), (&-1, &-11), (&0, &-32)]
+        );
+        assert_eq!(reversed.check_invariants(), Ok(()));
+    }
+}
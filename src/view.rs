@@ -0,0 +1,167 @@
+//! Filtered views of an instance, see [`SortedGroups::view`].
+use std::{borrow::Borrow, sync::OnceLock};
+
+use crate::{GroupStorage, SortedGroups};
+
+/// View of the elements of a [`SortedGroups`] matching a predicate, returned by
+/// [`SortedGroups::view`].
+///
+/// Flat indices and lengths only account for matching elements, and groups without matching
+/// elements are skipped.
+pub struct SortedGroupsView<'a, G, E, S, F>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    sorted_groups: &'a SortedGroups<G, E, S>,
+    pred: F,
+    // Number of matching elements before each group, followed by the number of matching
+    // elements, computed on demand
+    offsets: OnceLock<Vec<usize>>,
+}
+
+impl<G, E, S> SortedGroups<G, E, S>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+{
+    /// Returns a view of the elements for which `pred` returns `true`.
+    ///
+    /// The view borrows `self`, so it always reflects its current elements without copying them.
+    /// Counting and indexing walk all elements once, the offsets of matching elements in each
+    /// group are then cached, so that indexing only walks the group of the element.
+    pub fn view<F>(&self, pred: F) -> SortedGroupsView<'_, G, E, S, F>
+    where
+        F: Fn(&G, &E) -> bool,
+    {
+        SortedGroupsView {
+            sorted_groups: self,
+            pred,
+            offsets: OnceLock::new(),
+        }
+    }
+}
+
+impl<'a, G, E, S, F> SortedGroupsView<'a, G, E, S, F>
+where
+    G: Ord,
+    S: GroupStorage<E>,
+    F: Fn(&G, &E) -> bool,
+{
+    /// Returns the number of matching elements.
+    pub fn len(&self) -> usize {
+        self.offsets().last().copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of groups with matching elements.
+    pub fn groups_len(&self) -> usize {
+        self.offsets()
+            .windows(2)
+            .filter(|offsets| offsets[0] < offsets[1])
+            .count()
+    }
+
+    /// Returns an iterator over matching elements along with their group, in the order of the
+    /// `SortedGroups`.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&'a G, &'a E)> + '_ {
+        self.sorted_groups
+            .groups
+            .iter()
+            .flat_map(move |(group, elements)| self.matching(group, elements))
+    }
+
+    /// Returns an iterator over the matching elements of `group`.
+    pub fn iter_group<Q>(&self, group: &Q) -> impl DoubleEndedIterator<Item = &'a E> + '_
+    where
+        G: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let groups = &self.sorted_groups.groups;
+        let group = self
+            .sorted_groups
+            .group_index(group)
+            .ok()
+            .map(|index| &groups[index]);
+        group
+            .into_iter()
+            .flat_map(move |(group, elements)| self.matching(group, elements))
+            .map(|(_, element)| element)
+    }
+
+    /// Returns the matching element at the flat `index` among matching elements, along with its
+    /// group.
+    pub fn get(&self, index: usize) -> Option<(&'a G, &'a E)> {
+        if index >= self.len() {
+            return None;
+        }
+        // The last group starting at or before `index` holds it, groups without matching
+        // elements sharing the offset of the next one
+        let offsets = self.offsets();
+        let group_index = offsets.partition_point(|offset| *offset <= index) - 1;
+        let (group, elements) = &self.sorted_groups.groups[group_index];
+        self.matching(group, elements)
+            .nth(index - offsets[group_index])
+    }
+
+    fn matching(
+        &self,
+        group: &'a G,
+        elements: &'a S,
+    ) -> impl DoubleEndedIterator<Item = (&'a G, &'a E)> + '_ {
+        elements
+            .iter()
+            .filter(move |element| (self.pred)(group, element))
+            .map(move |element| (group, element))
+    }
+
+    fn offsets(&self) -> &[usize] {
+        self.offsets.get_or_init(|| {
+            let mut offset = 0;
+            let mut offsets = Vec::with_capacity(self.sorted_groups.groups.len() + 1);
+            offsets.push(0);
+            for (group, elements) in &self.sorted_groups.groups {
+                offset += self.matching(group, elements).count();
+                offsets.push(offset);
+            }
+            offsets
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SortedGroups;
+
+    #[test]
+    fn test_view() {
+        let sorted_groups =
+            SortedGroups::<i32, i32>::new(vec![11, 12, 13, 21, 23, 32, 34], |e| e / 10);
+        let odd = sorted_groups.view(|_, e| e % 2 == 1);
+        let even = sorted_groups.view(|_, e| e % 2 == 0);
+
+        assert_eq!(odd.len(), 4);
+        assert_eq!(odd.groups_len(), 2);
+        assert_eq!(
+            odd.iter().collect::<Vec<_>>(),
+            vec![(&1, &11), (&1, &13), (&2, &21), (&2, &23)]
+        );
+        assert_eq!(odd.get(2), Some((&2, &21)));
+        assert_eq!(odd.get(4), None);
+        assert_eq!(even.get(1), Some((&3, &32)));
+        assert_eq!(even.iter_group(&3).collect::<Vec<_>>(), vec![&32, &34]);
+        assert_eq!(even.iter().next_back(), Some((&3, &34)));
+        assert!(sorted_groups.view(|group, _| *group > 3).is_empty());
+        assert_eq!(sorted_groups.view(|group, _| *group > 3).get(0), None);
+
+        let mut sparse = SortedGroups::<i32, i32>::new(vec![11, 31, 33, 51], |e| e / 10);
+        sparse.add_group(2);
+        let view = sparse.view(|group, _| *group != 1);
+        assert_eq!(view.get(0), Some((&3, &31)));
+        assert_eq!(view.get(2), Some((&5, &51)));
+        assert_eq!(view.groups_len(), 2);
+    }
+}